@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use crate::nucleotide::{is_rna, reverse_complement};
+use crate::unitig::Unitig;
+
+pub struct BiEdge {
+    // An edge between two canonical k-mers. Each end records whether the
+    // (k+1)-mer reads the k-mer as it is (forward) or its reverse complement
+    pub from: usize,
+    pub from_forward: bool,
+    pub to: usize,
+    pub to_forward: bool,
+    pub count: usize,
+}
+
+pub struct BidirectedGraph {
+    // De Bruijn graph with one node per canonical k-mer, the smaller of a
+    // k-mer and its reverse complement, so that both strands share the same
+    // nodes and edges.
+    //
+    // Walks go through oriented nodes, numbered `2 * node` for the canonical
+    // k-mer and `2 * node + 1` for its reverse complement, whose labels are
+    // kept in `labels`. Every edge can be
+    // traversed forward, or backward from the twin of its end to the twin of
+    // its start
+    pub edges: Vec<BiEdge>,
+    labels: Vec<String>,
+    steps: Vec<Vec<(usize, usize)>>,
+    in_degree: Vec<usize>,
+    out_degree: Vec<usize>,
+}
+
+fn canonical(kmer: &str, rna: bool) -> Result<(String, bool), String> {
+    // Return the canonical form of a k-mer and whether it is the k-mer itself
+    let rc = reverse_complement(kmer, rna)?;
+    if kmer <= rc.as_str() {
+        Ok((kmer.to_string(), true))
+    } else {
+        Ok((rc, false))
+    }
+}
+
+fn twin(oriented: usize) -> usize {
+    oriented ^ 1
+}
+
+impl BidirectedGraph {
+    pub fn from_edge_counts(edges: HashMap<(String, String), usize>) -> Result<Self, String> {
+        // Create a graph from the edges of a de Bruijn graph built with
+        // reverse complements, as returned by `count_edges`. An edge and its
+        // reverse complement are merged into one edge
+        //
+        // # Arguments
+        // * `edges` - A map from each edge to the number of times it was seen
+        //
+        // # Returns
+        // A new BidirectedGraph or an error if a k-mer has invalid characters
+        // or an edge was seen more often on one strand than on the other
+        //
+        // Each (k+1)-mer arrives twice, as itself and as its reverse
+        // complement, with the count of each strand. A reverse complement
+        // palindrome arrives once, with the counts of both strands added up.
+        // An edge left on one strand only, as simplification can do, keeps
+        // the count of that strand. Whether the k-mers are RNA is decided
+        // once, from all of them
+        let rna = is_rna(edges.keys().flat_map(|(k, v)| [k.as_str(), v.as_str()]));
+        let mut strand_counts: HashMap<String, (usize, usize)> = HashMap::new();
+        for ((k, v), count) in edges {
+            let kmer = format!("{}{}", k, &v[v.len() - 1..]);
+            let (kmer, forward) = canonical(&kmer, rna)?;
+            let counts = strand_counts.entry(kmer).or_insert((0, 0));
+            if forward {
+                counts.0 += count;
+            } else {
+                counts.1 += count;
+            }
+        }
+
+        let mut canonical_edges = Vec::with_capacity(strand_counts.len());
+        for (kmer, (forward, backward)) in strand_counts {
+            let count = if kmer == reverse_complement(&kmer, rna)? {
+                (forward % 2 == 0).then_some(forward / 2)
+            } else if forward == 0 || backward == 0 || forward == backward {
+                Some(forward.max(backward))
+            } else {
+                None
+            };
+            match count {
+                Some(count) => canonical_edges.push((kmer, count)),
+                None => {
+                    return Err(format!(
+                        "Edge {} was not seen as often on both strands",
+                        kmer
+                    ))
+                }
+            }
+        }
+        canonical_edges.sort();
+
+        let mut ends = Vec::with_capacity(canonical_edges.len());
+        let mut nodes = Vec::new();
+        for (kmer, _) in &canonical_edges {
+            let from = canonical(&kmer[..kmer.len() - 1], rna)?;
+            let to = canonical(&kmer[1..], rna)?;
+            nodes.push(from.0.clone());
+            nodes.push(to.0.clone());
+            ends.push((from, to));
+        }
+        nodes.sort();
+        nodes.dedup();
+        let index: HashMap<&String, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node, i))
+            .collect();
+
+        let edges: Vec<BiEdge> = ends
+            .into_iter()
+            .zip(&canonical_edges)
+            .map(
+                |(((from, from_forward), (to, to_forward)), (_, count))| BiEdge {
+                    from: index[&from],
+                    from_forward,
+                    to: index[&to],
+                    to_forward,
+                    count: *count,
+                },
+            )
+            .collect();
+
+        let mut labels = Vec::with_capacity(2 * nodes.len());
+        for node in &nodes {
+            labels.push(node.clone());
+            labels.push(reverse_complement(node, rna)?);
+        }
+
+        let mut steps = vec![Vec::new(); labels.len()];
+        let mut in_degree = vec![0; labels.len()];
+        let mut out_degree = vec![0; labels.len()];
+        for (e, edge) in edges.iter().enumerate() {
+            let from = 2 * edge.from + !edge.from_forward as usize;
+            let to = 2 * edge.to + !edge.to_forward as usize;
+            steps[from].push((e, to));
+            out_degree[from] += edge.count;
+            in_degree[to] += edge.count;
+            // A reverse complement palindrome is its own backward traversal.
+            // It has one step, from a k-mer to its twin, but its two ends
+            // both count towards the degrees, as for any other edge
+            if (twin(to), twin(from)) != (from, to) {
+                steps[twin(to)].push((e, twin(from)));
+            }
+            out_degree[twin(to)] += edge.count;
+            in_degree[twin(from)] += edge.count;
+        }
+
+        Ok(Self {
+            edges,
+            labels,
+            steps,
+            in_degree,
+            out_degree,
+        })
+    }
+
+    fn is_one_in_one_out(&self, oriented: usize) -> bool {
+        self.steps[oriented].len() == 1 && self.steps[twin(oriented)].len() == 1
+    }
+
+    fn extend_unitig(&self, from: usize, step: (usize, usize), used: &mut [bool]) -> Unitig {
+        // Follow the oriented nodes from `from` for as long as the path does
+        // not branch, marking each edge so that the twin of the unitig, on the
+        // other strand, is not reported again
+        //
+        // # Arguments
+        // * `from` - The oriented node the unitig starts at
+        // * `step` - The first edge of the unitig and the oriented node it leads to
+        // * `used` - The edges already placed in a unitig, updated in place
+        //
+        // # Returns
+        // The unitig starting with the `step` edge
+        let mut seq = self.labels[from].clone();
+        let mut edges = Vec::new();
+
+        let (mut e, mut to) = step;
+        while !used[e] {
+            used[e] = true;
+            edges.push(e);
+            seq.push_str(&self.labels[to][self.labels[to].len() - 1..]);
+
+            if !self.is_one_in_one_out(to) {
+                break;
+            }
+            (e, to) = self.steps[to][0];
+        }
+        Unitig { seq, edges }
+    }
+
+    pub fn find_unitigs(&self) -> Vec<Unitig> {
+        // Compact the graph into its maximal non-branching paths. A unitig
+        // and its reverse complement cover the same edges, so only the first
+        // one found is returned
+        //
+        // # Returns
+        // A vector with the unitigs, covering every edge of the graph once
+        let mut used = vec![false; self.edges.len()];
+        let mut unitigs = Vec::new();
+
+        for from in 0..self.labels.len() {
+            if self.is_one_in_one_out(from) {
+                continue;
+            }
+            for &step in &self.steps[from] {
+                if !used[step.0] {
+                    unitigs.push(self.extend_unitig(from, step, &mut used));
+                }
+            }
+        }
+        for from in 0..self.labels.len() {
+            for &step in &self.steps[from] {
+                if !used[step.0] {
+                    unitigs.push(self.extend_unitig(from, step, &mut used));
+                }
+            }
+        }
+        unitigs
+    }
+
+    pub fn coverage(&self, unitig: &Unitig) -> f64 {
+        // Mean number of times the (k+1)-mers of a unitig were seen
+        let total: usize = unitig.edges.iter().map(|&e| self.edges[e].count).sum();
+        total as f64 / unitig.edges.len() as f64
+    }
+
+    fn find_start_node(&self) -> Result<(usize, bool), String> {
+        // Check the balance conditions for an Eulerian walk on the oriented
+        // nodes. A path starting at one oriented node ends at another one or
+        // at its own twin, so a path has two candidate starts: the start of
+        // the walk and the twin of its end
+        //
+        // # Returns
+        // The oriented start node and whether the walk is a cycle, or an
+        // error if the graph is unbalanced
+        let mut starts = Vec::new();
+        for oriented in 0..self.labels.len() {
+            let (count_in, count_out) = (self.in_degree[oriented], self.out_degree[oriented]);
+            if count_out == count_in + 1 {
+                starts.push(oriented);
+            } else if count_out != count_in && count_in != count_out + 1 {
+                return Err(format!(
+                    "Graph is unbalanced at node {} (in: {}, out: {})",
+                    self.labels[oriented], count_in, count_out
+                ));
+            }
+        }
+
+        match starts.len() {
+            0 => {
+                let start = (0..self.labels.len())
+                    .find(|&oriented| !self.steps[oriented].is_empty())
+                    .ok_or("No edges provided")?;
+                Ok((start, true))
+            }
+            1 | 2 => Ok((starts[0], false)),
+            _ => Err(format!(
+                "Graph is unbalanced: {} nodes have more outgoing than incoming edges",
+                starts.len()
+            )),
+        }
+    }
+
+    pub fn find_eulerian_walk(&self) -> Result<(Vec<&str>, bool), String> {
+        // Find a walk that uses every edge of the graph, on either strand, as
+        // many times as it was seen, following Hierholzer's algorithm over
+        // the oriented nodes
+        //
+        // # Returns
+        // The k-mers visited, in order and oriented as they are read in the
+        // walk, and whether the walk is a cycle. Fails if the graph is
+        // unbalanced or its edges are not connected
+        let (start, is_cycle) = self.find_start_node()?;
+
+        let mut remaining: Vec<usize> = self.edges.iter().map(|edge| edge.count).collect();
+        let total: usize = remaining.iter().sum();
+        let mut next_step = vec![0; self.labels.len()];
+        let mut stack = vec![start];
+        let mut walk = Vec::with_capacity(total + 1);
+
+        while let Some(&v) = stack.last() {
+            while next_step[v] < self.steps[v].len()
+                && remaining[self.steps[v][next_step[v]].0] == 0
+            {
+                next_step[v] += 1;
+            }
+            if next_step[v] < self.steps[v].len() {
+                let (e, to) = self.steps[v][next_step[v]];
+                remaining[e] -= 1;
+                stack.push(to);
+            } else {
+                walk.push(stack.pop().unwrap());
+            }
+        }
+        walk.reverse();
+
+        if walk.len() != total + 1 {
+            return Err(format!(
+                "Graph is not connected: the walk covers {} of {} edges",
+                walk.len() - 1,
+                total
+            ));
+        }
+
+        Ok((
+            walk.into_iter().map(|v| self.labels[v].as_str()).collect(),
+            is_cycle,
+        ))
+    }
+}
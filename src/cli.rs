@@ -1,6 +1,10 @@
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+
+use crate::graph_format::GraphFormat;
+use crate::superstring::LayoutFormat;
 
 #[derive(Parser)]
 #[command(
@@ -16,6 +20,82 @@ pub struct Cli {
     pub command: Commands,
 }
 
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum ColoredQuery {
+    /// Every edge with the samples it was seen in
+    Edges,
+    /// The k-mers seen in every sample
+    Shared,
+    /// The k-mers seen in a single sample
+    Unique,
+    /// The bubbles whose branches were seen in different samples
+    Bubbles,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum RevcompMode {
+    /// Reverse the sequence
+    Reverse,
+    /// Complement each base
+    Complement,
+    /// Reverse the sequence and complement each base
+    ReverseComplement,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum GraphQuery {
+    /// The k-mers following a k-mer
+    Successors,
+    /// The k-mers preceding a k-mer
+    Predecessors,
+    /// The in and out degree of a k-mer, or of every k-mer
+    Degree,
+    /// The k-mers containing a pattern, with their degrees
+    Search,
+    /// The weakly connected components of the graph
+    Components,
+    /// The simple cycles of the graph
+    Cycles,
+}
+
+#[derive(Subcommand)]
+pub enum DebruijnCommand {
+    /// Answer a query about the De Bruijn graph of the input sequences
+    Query {
+        /// The query to answer
+        #[arg(value_enum)]
+        query: GraphQuery,
+        /// The input sequence (optional, reads from stdin if not present)
+        #[clap()]
+        input: Option<PathBuf>,
+        /// The length of the nodes. Every (k+1)-mer of each read becomes an edge (default: each whole line is one edge)
+        #[arg(short)]
+        k: Option<usize>,
+        /// Include reverse complements of the sequences in the graph construction
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        rc: bool,
+        /// The k-mer to query, or the pattern to search for
+        #[arg(long)]
+        kmer: Option<String>,
+        /// The maximum number of cycles returned
+        #[arg(long, default_value_t = 100, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        limit: usize,
+    },
+}
+
+#[derive(Args)]
+pub struct SimplifyArgs {
+    /// Remove the edges seen fewer than this many times
+    #[arg(long)]
+    pub min_coverage: Option<usize>,
+    /// Clip dead-end tips shorter than this many bases
+    #[arg(long)]
+    pub tip_length: Option<usize>,
+    /// Pop bubbles, keeping the branch with the highest coverage, whose other branches are at most this many bases long
+    #[arg(long)]
+    pub bubble_length: Option<usize>,
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
     /// Return the number of times each kmer appears in the input sequence
@@ -32,30 +112,200 @@ pub enum Commands {
         /// The input sequencs (optional, reads from stdin if not present)
         #[clap(default_value = "-")]
         input: PathBuf,
-        /// The length of overlap between sequences
+        /// The length of overlap between sequences (minimum length when errors are allowed)
+        #[arg(short)]
+        k: usize,
+        /// Maximum number of mismatches or indels allowed in an overlap
+        #[arg(short = 'e', long)]
+        max_errors: Option<usize>,
+        /// Maximum fraction of mismatches or indels allowed in an overlap
+        #[arg(long)]
+        max_error_rate: Option<f64>,
+        /// Also check reverse complement orientations and report the orientation of each edge
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        rc: bool,
+        /// The output format
+        #[arg(short, long, value_enum, default_value_t = GraphFormat::Text)]
+        format: GraphFormat,
+        /// Draw nodes as unlabelled points in DOT output, for large graphs
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        collapse_labels: bool,
+        /// Write each overlap as a PAF record instead of the graph
+        #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with_all = ["format", "collapse_labels"])]
+        paf: bool,
+    },
+    /// Return the string graph of the input sequences, without contained reads and transitive edges
+    StringGraph {
+        /// The input sequences (optional, reads from stdin if not present)
+        #[clap()]
+        input: Option<PathBuf>,
+        /// The minimum length of overlap between sequences
         #[arg(short)]
         k: usize,
+        /// Maximum number of mismatches or indels allowed in an overlap
+        #[arg(short = 'e', long)]
+        max_errors: Option<usize>,
+        /// Maximum fraction of mismatches or indels allowed in an overlap
+        #[arg(long)]
+        max_error_rate: Option<f64>,
+        /// Also check reverse complement orientations and report the orientation of each edge
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        rc: bool,
+        /// Slack, in bases, allowed when comparing overhang lengths during the transitive reduction
+        #[arg(long, default_value_t = 10)]
+        fuzz: usize,
+        /// The output format
+        #[arg(short, long, value_enum, default_value_t = GraphFormat::Text)]
+        format: GraphFormat,
+        /// Draw nodes as unlabelled points in DOT output, for large graphs
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        collapse_labels: bool,
+        /// Write each overlap as a PAF record instead of the graph
+        #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with_all = ["format", "collapse_labels"])]
+        paf: bool,
     },
     /// Return the shortest possible superstring containing all the input sequences
     Superstring {
         /// The input sequencs (optional, reads from stdin if not present)
         #[clap()]
         input: Option<PathBuf>,
+        /// Write the position of each input record in the superstring to this file
+        #[arg(long)]
+        layout: Option<PathBuf>,
+        /// The format of the layout report
+        #[arg(long, value_enum, default_value_t = LayoutFormat::Tsv)]
+        layout_format: LayoutFormat,
+        /// Only merge sequences that overlap by at least this many bases, and return one contig per chain as multi-FASTA
+        #[arg(long)]
+        min_overlap: Option<usize>,
+        /// Return every distinct shortest superstring, one per line
+        #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with_all = ["layout", "min_overlap"])]
+        all: bool,
+        /// The maximum number of superstrings returned with --all
+        #[arg(long, default_value_t = 100, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        limit: usize,
+    },
+    /// Reverse, complement or reverse complement DNA or RNA sequences, keeping IUPAC codes and case
+    Revcomp {
+        /// The input sequences as fasta, fastq or one per line (optional, reads from stdin if not present)
+        #[clap()]
+        input: Option<PathBuf>,
+        /// The transformation to apply
+        #[arg(short, long, value_enum, default_value_t = RevcompMode::ReverseComplement)]
+        mode: RevcompMode,
     },
     /// Constructs the De Bruijn graph from a set of DNA sequences and returns the adjacency list.
+    #[command(args_conflicts_with_subcommands = true)]
     Debruijn {
+        #[command(subcommand)]
+        command: Option<DebruijnCommand>,
         /// The input sequence (optional, reads from stdin if not present)
         #[clap()]
         input: Option<PathBuf>,
+        /// The length of the nodes. Every (k+1)-mer of each read becomes an edge (default: each whole line is one edge)
+        #[arg(short)]
+        k: Option<usize>,
+        /// Include reverse complements of the sequences in the graph construction
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        rc: bool,
+        /// The output format
+        #[arg(short, long, value_enum, default_value_t = GraphFormat::Text)]
+        format: GraphFormat,
+        /// Draw nodes as unlabelled points in DOT output, for large graphs
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        collapse_labels: bool,
+        /// Report the number of times each edge was seen, as `(X, Y) xN`
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        counts: bool,
+        #[command(flatten)]
+        simplify: SimplifyArgs,
+    },
+    /// Reconstruct a string from read pairs with the paired De Bruijn graph
+    PairedDebruijn {
+        /// One `AAG|GCA` read pair per line, optionally after a `k d` line (optional, reads from stdin if not present)
+        #[clap()]
+        input: Option<PathBuf>,
+        /// The number of bases between the two reads of a pair (default: the `k d` line of the input)
+        #[arg(short)]
+        d: Option<usize>,
+        /// Return the adjacency list of the paired De Bruijn graph
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        adjacency: bool,
+        /// Spell the pairs in the given order, as a gapped genome path, instead of assembling them
+        #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with = "adjacency")]
+        spell: bool,
+    },
+    /// Assemble reads into unitigs, the maximal non-branching paths of their De Bruijn graph
+    Contigs {
+        /// The input reads as fasta or one per line (optional, reads from stdin if not present)
+        #[clap()]
+        input: Option<PathBuf>,
+        /// The length of the nodes of the graph
+        #[arg(short)]
+        k: usize,
         /// Include reverse complements of the sequences in the graph construction
         #[arg(short, long, action = clap::ArgAction::SetTrue)]
         rc: bool,
+        #[command(flatten)]
+        simplify: SimplifyArgs,
+    },
+    /// Build one De Bruijn graph from several samples, tagging each k-mer with the samples it came from
+    Colored {
+        /// The input files, one sample each, as fasta or one sequence per line
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        /// The length of the nodes of the graph
+        #[arg(short)]
+        k: usize,
+        /// Take each fasta record as a sample, instead of each file
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        by_record: bool,
+        /// Include reverse complements of the sequences in the graph construction
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        rc: bool,
+        /// What to report from the graph
+        #[arg(short, long, value_enum, default_value_t = ColoredQuery::Edges)]
+        query: ColoredQuery,
+    },
+    /// Find an Eulerian path or cycle of a graph and spell the string it reconstructs
+    Eulerian {
+        /// An adjacency list with `A -> B,C` or `(A, B) [xN]` lines, or reads with -k (optional, reads from stdin if not present)
+        #[clap()]
+        input: Option<PathBuf>,
+        /// Read one sequence per line and build the de Bruijn graph of their (k+1)-mers
+        #[arg(short)]
+        k: Option<usize>,
+        /// With -k, build the graph from both strands of the reads and walk each edge on either strand
+        #[arg(short, long, action = clap::ArgAction::SetTrue, requires = "k")]
+        rc: bool,
+        /// Return the nodes of the walk instead of the spelled string
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        path: bool,
+        /// Spell Eulerian cycles as circular strings, without the characters that wrap around to the start
+        #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with = "path")]
+        circular: bool,
+    },
+    /// Generate a k-universal circular string, which contains every k-mer over an alphabet once
+    Universal {
+        /// The length of the k-mers
+        #[arg(short)]
+        k: usize,
+        /// The symbols the k-mers are made of
+        #[arg(short, long, default_value = "ACGT")]
+        alphabet: String,
+        /// Return a linear string containing every k-mer, with the first k-1 symbols repeated at the end
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        linear: bool,
     },
     /// Constructs the shortest possible cyclic superstring from a collection of DNA sequences
     PerfectAssembly {
         /// The input sequence (optional, reads from stdin if not present)
         #[clap()]
         input: Option<PathBuf>,
+        /// The reads come from both strands: search decreasing k until the graph of the reads
+        /// and their reverse complements is two cycles, one per strand
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        rc: bool,
     },
     /// Given a collection of reads, ouput the N50 and N75 assembly quality metrics
     AssemblyQuality {
@@ -64,3 +314,26 @@ pub enum Commands {
         input: Option<PathBuf>,
     },
 }
+
+pub fn check_collapse_labels(subcommand: &str, collapse_labels: bool, dot: bool) {
+    // Reject --collapse-labels for formats other than DOT. clap can make an
+    // argument conflict with another argument but not with one of its
+    // values, so the check runs after parsing and exits with a clap error
+    //
+    // # Arguments
+    // * `subcommand` - The name of the subcommand the arguments belong to
+    // * `collapse_labels` - Whether --collapse-labels was given
+    // * `dot` - Whether the output format is DOT
+    if collapse_labels && !dot {
+        let mut command = Cli::command();
+        command.build();
+        command
+            .find_subcommand_mut(subcommand)
+            .unwrap()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--collapse-labels can only be used with --format dot",
+            )
+            .exit();
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::cli::ColoredQuery;
+use crate::debruijn::{count_edges, DeBruijnGraph};
+use crate::simplify::find_bubbles;
+
+pub struct Sample {
+    // A set of sequences sharing one color, from an input file or record
+    pub name: String,
+    pub sequences: Vec<String>,
+}
+
+struct ColoredGraph {
+    // De Bruijn graph of all the samples, with the samples each k-mer and
+    // each edge were seen in, by their index in `colors`
+    graph: DeBruijnGraph,
+    colors: Vec<String>,
+    node_colors: Vec<BTreeSet<usize>>,
+    edge_colors: Vec<BTreeSet<usize>>,
+}
+
+impl ColoredGraph {
+    fn new(samples: Vec<Sample>, k: usize, rc: bool) -> Result<Self, String> {
+        // Build the colored de Bruijn graph of a set of samples
+        //
+        // # Arguments
+        // * `samples` - The samples, one color each
+        // * `k` - The length of the nodes of the graph
+        // * `rc` - A boolean indicating whether to include the reverse complement of the sequences
+        //
+        // # Returns
+        // A new ColoredGraph or an error if a sample has a sequence shorter than k+1
+        let mut edges = HashMap::new();
+        let mut colors_of_edge: HashMap<(String, String), BTreeSet<usize>> = HashMap::new();
+        let mut colors = Vec::new();
+
+        for (color, sample) in samples.into_iter().enumerate() {
+            let sequences = sample.sequences.iter().map(|s| s.as_str()).collect();
+            let sample_edges = count_edges(sequences, Some(k), rc)
+                .map_err(|e| format!("{}: {}", sample.name, e))?;
+
+            for (edge, count) in sample_edges {
+                colors_of_edge
+                    .entry(edge.clone())
+                    .or_default()
+                    .insert(color);
+                *edges.entry(edge).or_insert(0) += count;
+            }
+            colors.push(sample.name);
+        }
+
+        let graph = DeBruijnGraph::from_edge_counts(edges);
+        let mut node_colors = vec![BTreeSet::new(); graph.nodes.len()];
+        let mut edge_colors = Vec::with_capacity(graph.edges.len());
+        for edge in &graph.edges {
+            let key = (graph.nodes[edge.from].clone(), graph.nodes[edge.to].clone());
+            let colors = colors_of_edge.remove(&key).unwrap();
+            node_colors[edge.from].extend(&colors);
+            node_colors[edge.to].extend(&colors);
+            edge_colors.push(colors);
+        }
+
+        Ok(Self {
+            graph,
+            colors,
+            node_colors,
+            edge_colors,
+        })
+    }
+
+    fn color_names(&self, colors: &BTreeSet<usize>) -> String {
+        colors
+            .iter()
+            .map(|&c| self.colors[c].as_str())
+            .collect::<Vec<&str>>()
+            .join(",")
+    }
+}
+
+fn generate_edges_output(colored: &ColoredGraph) -> String {
+    // List each edge with the samples it was seen in
+    let mut output = String::new();
+    for (edge, colors) in colored.graph.edges.iter().zip(&colored.edge_colors) {
+        output.push_str(&format!(
+            "({}, {})\t{}\n",
+            colored.graph.nodes[edge.from],
+            colored.graph.nodes[edge.to],
+            colored.color_names(colors)
+        ));
+    }
+    output
+}
+
+fn generate_shared_output(colored: &ColoredGraph) -> String {
+    // List the k-mers seen in every sample
+    let mut output = String::new();
+    for (kmer, colors) in colored.graph.nodes.iter().zip(&colored.node_colors) {
+        if colors.len() == colored.colors.len() {
+            output.push_str(&format!("{}\n", kmer));
+        }
+    }
+    output
+}
+
+fn generate_unique_output(colored: &ColoredGraph) -> String {
+    // List the k-mers seen in a single sample, with the sample
+    let mut output = String::new();
+    for (kmer, colors) in colored.graph.nodes.iter().zip(&colored.node_colors) {
+        if colors.len() == 1 {
+            output.push_str(&format!("{}\t{}\n", kmer, colored.color_names(colors)));
+        }
+    }
+    output
+}
+
+fn generate_bubbles_output(colored: &ColoredGraph) -> String {
+    // List the bubbles whose branches were seen in different samples. Each
+    // branch is written on its own line, with the samples that support all
+    // of its edges
+    //
+    // # Arguments
+    // * `colored` - The colored de Bruijn graph
+    //
+    // # Returns
+    // A string with the bubble number, start and end k-mers, branch
+    // sequence and samples of each branch
+    let graph = &colored.graph;
+    let mut output = String::new();
+    let mut number = 0;
+
+    for branches in find_bubbles(graph) {
+        let branch_colors: Vec<BTreeSet<usize>> = branches
+            .iter()
+            .map(|unitig| {
+                unitig
+                    .edges
+                    .iter()
+                    .map(|&e| colored.edge_colors[e].clone())
+                    .reduce(|a, b| a.intersection(&b).copied().collect())
+                    .unwrap()
+            })
+            .collect();
+        if branch_colors
+            .iter()
+            .all(|colors| colors == &branch_colors[0])
+        {
+            continue;
+        }
+
+        number += 1;
+        let start = &graph.nodes[graph.edges[branches[0].edges[0]].from];
+        let end = &graph.nodes[graph.edges[*branches[0].edges.last().unwrap()].to];
+        for (unitig, colors) in branches.iter().zip(&branch_colors) {
+            output.push_str(&format!(
+                "bubble_{}\t{}\t{}\t{}\t{}\n",
+                number,
+                start,
+                end,
+                unitig.seq,
+                colored.color_names(colors)
+            ));
+        }
+    }
+    output
+}
+
+pub fn run_colored_graph(
+    samples: Vec<Sample>,
+    k: usize,
+    rc: bool,
+    query: ColoredQuery,
+) -> Result<String, String> {
+    // Build the colored de Bruijn graph of a set of samples and run a query
+    // on it
+    //
+    // # Arguments
+    // * `samples` - The samples, one color each
+    // * `k` - The length of the nodes of the graph
+    // * `rc` - A boolean indicating whether to include the reverse complement of the sequences
+    // * `query` - What to report from the graph
+    //
+    // # Returns
+    // Result containing the query output or an error message
+    if samples.is_empty() {
+        return Err("No samples provided".to_string());
+    }
+    let colored = ColoredGraph::new(samples, k, rc)?;
+
+    match query {
+        ColoredQuery::Edges => Ok(generate_edges_output(&colored)),
+        ColoredQuery::Shared => Ok(generate_shared_output(&colored)),
+        ColoredQuery::Unique => Ok(generate_unique_output(&colored)),
+        ColoredQuery::Bubbles => Ok(generate_bubbles_output(&colored)),
+    }
+}
@@ -0,0 +1,64 @@
+use crate::bidirected::BidirectedGraph;
+use crate::cli::SimplifyArgs;
+use crate::debruijn::{count_edges, DeBruijnGraph};
+use crate::simplify::simplify_graph;
+use crate::unitig::find_unitigs;
+
+fn generate_fasta_output(unitigs: &[(String, f64)]) -> String {
+    // Write each unitig as a fasta record with its length and mean coverage
+    let mut output = String::new();
+    for (i, (seq, coverage)) in unitigs.iter().enumerate() {
+        output.push_str(&format!(
+            ">unitig_{} len={} cov={:.2}\n{}\n",
+            i + 1,
+            seq.len(),
+            coverage,
+            seq
+        ));
+    }
+    output
+}
+
+pub fn run_contigs(
+    sequences: Vec<&str>,
+    k: usize,
+    rc: bool,
+    simplify: &SimplifyArgs,
+) -> Result<String, String> {
+    // Build the de Bruijn graph of a set of reads, simplify it and return its
+    // unitigs
+    //
+    // # Arguments
+    // * `sequences` - A vector of strings containing sequences
+    // * `k` - The length of the nodes of the graph
+    // * `rc` - Whether to build the graph from both strands. Each unitig is
+    //          then returned once, on either strand
+    // * `simplify` - The graph simplification passes to run
+    //
+    // # Returns
+    // Result containing the unitigs as multi-fasta or an error message
+    let mut graph = DeBruijnGraph::from_edge_counts(count_edges(sequences, Some(k), rc)?);
+    simplify_graph(&mut graph, simplify);
+
+    let unitigs: Vec<(String, f64)> = if rc {
+        let graph = BidirectedGraph::from_edge_counts(graph.edge_counts())?;
+        graph
+            .find_unitigs()
+            .into_iter()
+            .map(|unitig| {
+                let coverage = graph.coverage(&unitig);
+                (unitig.seq, coverage)
+            })
+            .collect()
+    } else {
+        find_unitigs(&graph)
+            .into_iter()
+            .map(|unitig| {
+                let coverage = unitig.coverage(&graph);
+                (unitig.seq, coverage)
+            })
+            .collect()
+    };
+
+    Ok(generate_fasta_output(&unitigs))
+}
@@ -1,94 +1,544 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 
-pub fn reverse_complement(seq: &str) -> Result<String, String> {
-    // Given a sequence, return the reverse complement
+use crate::cli::{GraphQuery, SimplifyArgs};
+use crate::graph_format::{check_gfa_name, dot_quote, GraphFormat};
+use crate::nucleotide::{is_rna, reverse_complement};
+use crate::simplify::simplify_graph;
+
+// Number of edges the cycle search may follow for each cycle requested with
+// `--limit`. The search is bounded by the edges followed and not only by the
+// cycles found, since many paths can lead to no cycle
+const STEPS_PER_CYCLE: usize = 10_000;
+
+fn add_edges(
+    edges: &mut HashMap<(String, String), usize>,
+    seq: &str,
+    k: Option<usize>,
+) -> Result<(), String> {
+    // Add the edges spelled by a sequence to the edge counts
     //
     // # Arguments
+    // * `edges` - The number of times each edge was seen, updated in place
     // * `seq` - A string containing a sequence
+    // * `k` - The length of the nodes. If None, the whole sequence is one edge
     //
     // # Returns
-    // A string containing the reverse complement of the input sequence
-    let mut result = String::new();
-    for c in seq.chars().rev() {
-        match c {
-            'A' => result.push('T'),
-            'T' => result.push('A'),
-            'C' => result.push('G'),
-            'G' => result.push('C'),
-            'N' => result.push('N'),
-            _ => Err(format!("Invalid character in sequence: {}", c))?,
+    // An empty Ok() or an error if the sequence is too short
+    if let Some(k) = k {
+        if seq.len() < k + 1 {
+            return Err(format!(
+                "Sequence {} is shorter than k+1 ({} < {})",
+                seq,
+                seq.len(),
+                k + 1
+            ));
         }
     }
-    Ok(result)
+    let k = k.unwrap_or(seq.len().saturating_sub(1));
+    if k == 0 {
+        return Err(format!("Sequence too short: {}", seq));
+    }
+
+    for i in 0..(seq.len() - k) {
+        let edge = (seq[i..i + k].to_string(), seq[i + 1..i + k + 1].to_string());
+        *edges.entry(edge).or_insert(0) += 1;
+    }
+    Ok(())
 }
 
-pub fn build_nodes(sequences: Vec<&str>, rc: bool) -> Result<HashSet<(String, String)>, String> {
-    // Given a vector of sequences, return a set of nodes
+pub fn count_edges(
+    sequences: Vec<&str>,
+    k: Option<usize>,
+    rc: bool,
+) -> Result<HashMap<(String, String), usize>, String> {
+    // Given a vector of sequences, return the edges of the de Bruijn graph with
+    // the number of times each one was seen. Every (k+1)-mer of a sequence is
+    // an edge between its prefix and suffix k-mers
     //
     // # Arguments
     // * `sequences` - A vector of strings containing sequences
+    // * `k` - The length of the nodes. If None, each whole sequence is one (k+1)-mer
     // * `rc` - A boolean indicating whether to include the reverse complement of the sequences
     //
     // # Returns
-    // A set of nodes
-    let mut nodes = HashSet::<(String, String)>::new();
+    // A map from each edge to its multiplicity
+    if k == Some(0) {
+        return Err("The length of the nodes must be at least 1".to_string());
+    }
+    let mut edges = HashMap::new();
 
     if sequences.is_empty() {
         return Err("No sequences provided".to_string());
     }
+    let rna = is_rna(sequences.iter().copied());
     for seq in sequences {
-        if seq.len() < 2 {
-            return Err(format!("Sequence too short: {}", seq));
+        // k-mers are sliced by byte
+        if !seq.is_ascii() {
+            return Err(format!("Invalid sequence: \"{}\"", seq));
         }
+        add_edges(&mut edges, seq, k)?;
 
-        let seq_nodes = (seq[0..(&seq.len() - 1)].to_string(), seq[1..].to_string());
-
-        if !nodes.contains(&seq_nodes) {
-            nodes.insert(seq_nodes);
+        if rc {
+            add_edges(&mut edges, &reverse_complement(seq, rna)?, k)?;
         }
+    }
 
-        if rc {
-            let rc_seq = reverse_complement(seq);
-            match rc_seq {
-                Ok(rc_seq) => {
-                    let rc_seq_nodes = (
-                        rc_seq[0..(&rc_seq.len() - 1)].to_string(),
-                        rc_seq[1..].to_string(),
-                    );
-                    if !nodes.contains(&rc_seq_nodes) {
-                        nodes.insert(rc_seq_nodes);
-                    }
-                }
-                Err(e) => return Err(e),
-            }
+    Ok(edges)
+}
+
+fn generate_gfa_output(edges_sorted: &[((String, String), usize)]) -> Result<String, String> {
+    // Generate a GFA 1.0 output from a sorted list of edges. Each k-mer is
+    // written once as a segment, and each edge as a link overlapping by k-1
+    // bases. Links carry the edge multiplicity (EC) and segments the k-mer
+    // count (KC), taken as the larger of their incoming and outgoing counts.
+    // A k-mer at the start of a read has no incoming edge from it, and one at
+    // the end no outgoing edge, so KC can be lower than the number of times
+    // the k-mer was seen
+    //
+    // # Arguments
+    // * `edges_sorted` - A vector of edges and their counts, sorted alphabetically
+    //
+    // # Returns
+    // A string with a header line, one S line per k-mer and one L line per
+    // edge, or an error if a k-mer is not a valid segment name
+    let mut kmer_counts: HashMap<&String, (usize, usize)> = HashMap::new();
+    for ((k, v), count) in edges_sorted {
+        kmer_counts.entry(k).or_default().1 += count;
+        kmer_counts.entry(v).or_default().0 += count;
+    }
+    let mut kmers: Vec<(&String, (usize, usize))> = kmer_counts.into_iter().collect();
+    kmers.sort();
+
+    let mut output = String::from("H\tVN:Z:1.0\n");
+    for (kmer, (count_in, count_out)) in kmers {
+        check_gfa_name(kmer)?;
+        output.push_str(&format!(
+            "S\t{}\t{}\tKC:i:{}\n",
+            kmer,
+            kmer,
+            count_in.max(count_out)
+        ));
+    }
+    for ((k, v), count) in edges_sorted {
+        output.push_str(&format!(
+            "L\t{}\t+\t{}\t+\t{}M\tEC:i:{}\n",
+            k,
+            v,
+            k.len() - 1,
+            count
+        ));
+    }
+
+    Ok(output)
+}
+
+fn generate_dot_output(
+    edges_sorted: &[((String, String), usize)],
+    collapse_labels: bool,
+    counts: bool,
+) -> String {
+    // Generate a DOT output from a sorted list of edges, with each edge
+    // labelled with the k-mer it represents
+    //
+    // # Arguments
+    // * `edges_sorted` - A vector of edges and their counts, sorted alphabetically
+    // * `collapse_labels` - Whether to draw the nodes as points without labels
+    // * `counts` - Whether to add the number of times each edge was seen to its label
+    //
+    // # Returns
+    // A string containing the DOT digraph
+    let mut output = String::from("digraph debruijn {\n");
+    if collapse_labels {
+        output.push_str("    node [shape=point, label=\"\"];\n");
+    }
+
+    for ((k, v), count) in edges_sorted.iter() {
+        let mut label = format!("{}{}", k, &v[v.len() - 1..]);
+        if counts {
+            label.push_str(&format!(" x{}", count));
         }
+        output.push_str(&format!(
+            "    {} -> {} [label={}];\n",
+            dot_quote(k),
+            dot_quote(v),
+            dot_quote(&label)
+        ));
     }
+    output.push_str("}\n");
 
-    Ok(nodes)
+    output
 }
 
-pub fn run_debruijn_graph(sequences: Vec<&str>, rc: bool) -> Result<String, String> {
+pub fn run_debruijn_graph(
+    sequences: Vec<&str>,
+    k: Option<usize>,
+    rc: bool,
+    format: GraphFormat,
+    collapse_labels: bool,
+    counts: bool,
+    simplify: &SimplifyArgs,
+) -> Result<String, String> {
     // Run the debuijn graph algorithm on a vector of sequences and return the nodes
     // as a string
     //
     // # Arguments
     // * `sequences` - A vector of strings containing sequences
+    // * `k` - The length of the nodes. If None, each whole sequence is one (k+1)-mer
     // * `rc` - A boolean indicating whether to include the reverse complement of the sequences
+    // * `format` - The output format
+    // * `collapse_labels` - Whether to draw unlabelled nodes in DOT output
+    // * `counts` - Whether to report the number of times each edge was seen
+    // * `simplify` - The graph simplification passes to run
     //
     // # Returns
     // A string containing the nodes of the graph, sorted alphabetically and separated by newlines
-    let nodes = build_nodes(sequences, rc);
-    match nodes {
-        Ok(nodes) => {
-            let mut nodes_sorted: Vec<(String, String)> = nodes.into_iter().collect();
-            nodes_sorted.sort();
-            let mut nodes_in_str = String::new();
-            for (k, v) in nodes_sorted {
-                nodes_in_str.push_str(&format!("({}, {})\n", k, v));
+    let mut edges = count_edges(sequences, k, rc)?;
+    if simplify.min_coverage.is_some()
+        || simplify.tip_length.is_some()
+        || simplify.bubble_length.is_some()
+    {
+        let mut graph = DeBruijnGraph::from_edge_counts(edges);
+        simplify_graph(&mut graph, simplify);
+        edges = graph.edge_counts();
+    }
+
+    let mut edges_sorted: Vec<((String, String), usize)> = edges.into_iter().collect();
+    edges_sorted.sort();
+    match format {
+        GraphFormat::Gfa => return generate_gfa_output(&edges_sorted),
+        GraphFormat::Dot => return Ok(generate_dot_output(&edges_sorted, collapse_labels, counts)),
+        GraphFormat::Text => {}
+    }
+    let mut nodes_in_str = String::new();
+    for ((k, v), count) in edges_sorted {
+        if counts {
+            nodes_in_str.push_str(&format!("({}, {}) x{}\n", k, v, count));
+        } else {
+            nodes_in_str.push_str(&format!("({}, {})\n", k, v));
+        }
+    }
+
+    Ok(nodes_in_str)
+}
+
+pub struct Edge {
+    // An edge of the de Bruijn graph and the number of times it was seen
+    pub from: usize,
+    pub to: usize,
+    pub count: usize,
+}
+
+pub struct DeBruijnGraph {
+    // De Bruijn graph with the k-mers as nodes, indexed by their position in
+    // `nodes`, and incoming and outgoing edge lists for each node
+    pub nodes: Vec<String>,
+    pub edges: Vec<Edge>,
+    out_edges: Vec<Vec<usize>>,
+    in_edges: Vec<Vec<usize>>,
+}
+
+impl DeBruijnGraph {
+    pub fn from_edge_counts(edges: HashMap<(String, String), usize>) -> Self {
+        // Create a graph from a map of edges to their multiplicities, as
+        // returned by `count_edges`. Nodes and edges are numbered in
+        // alphabetical order
+        //
+        // # Arguments
+        // * `edges` - A map from each edge to the number of times it was seen
+        //
+        // # Returns
+        // A new DeBruijnGraph
+        let mut edges_sorted: Vec<((String, String), usize)> = edges.into_iter().collect();
+        edges_sorted.sort();
+
+        let mut nodes: Vec<String> = edges_sorted
+            .iter()
+            .flat_map(|((k, v), _)| [k.clone(), v.clone()])
+            .collect();
+        nodes.sort();
+        nodes.dedup();
+        let index: HashMap<&String, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node, i))
+            .collect();
+
+        let edges = edges_sorted
+            .into_iter()
+            .map(|((k, v), count)| Edge {
+                from: index[&k],
+                to: index[&v],
+                count,
+            })
+            .collect();
+
+        let mut graph = Self {
+            nodes,
+            edges,
+            out_edges: Vec::new(),
+            in_edges: Vec::new(),
+        };
+        graph.index_edges();
+        graph
+    }
+
+    fn index_edges(&mut self) {
+        // Rebuild the incoming and outgoing edge lists of every node
+        self.out_edges = vec![Vec::new(); self.nodes.len()];
+        self.in_edges = vec![Vec::new(); self.nodes.len()];
+        for (e, edge) in self.edges.iter().enumerate() {
+            self.out_edges[edge.from].push(e);
+            self.in_edges[edge.to].push(e);
+        }
+    }
+
+    pub fn remove_edges(&mut self, removed: &[bool]) {
+        // Remove the edges flagged in `removed`. The remaining edges are
+        // renumbered, while the nodes keep their indices
+        let edges = std::mem::take(&mut self.edges);
+        self.edges = edges
+            .into_iter()
+            .zip(removed)
+            .filter(|(_, &removed)| !removed)
+            .map(|(edge, _)| edge)
+            .collect();
+        self.index_edges();
+    }
+
+    pub fn edge_counts(&self) -> HashMap<(String, String), usize> {
+        // Return the edges with their multiplicities, as from `count_edges`
+        self.edges
+            .iter()
+            .map(|edge| {
+                (
+                    (self.nodes[edge.from].clone(), self.nodes[edge.to].clone()),
+                    edge.count,
+                )
+            })
+            .collect()
+    }
+
+    pub fn out_edges(&self, node: usize) -> &[usize] {
+        &self.out_edges[node]
+    }
+
+    pub fn in_edges(&self, node: usize) -> &[usize] {
+        &self.in_edges[node]
+    }
+
+    pub fn find_node(&self, kmer: &str) -> Option<usize> {
+        // Return the index of a k-mer, if it is a node of the graph
+        self.nodes
+            .binary_search_by(|node| node.as_str().cmp(kmer))
+            .ok()
+    }
+
+    pub fn successors(&self, node: usize) -> Vec<usize> {
+        self.out_edges[node]
+            .iter()
+            .map(|&e| self.edges[e].to)
+            .collect()
+    }
+
+    pub fn predecessors(&self, node: usize) -> Vec<usize> {
+        self.in_edges[node]
+            .iter()
+            .map(|&e| self.edges[e].from)
+            .collect()
+    }
+
+    pub fn in_degree(&self, node: usize) -> usize {
+        self.in_edges[node].len()
+    }
+
+    pub fn out_degree(&self, node: usize) -> usize {
+        self.out_edges[node].len()
+    }
+
+    pub fn connected_components(&self) -> Vec<Vec<usize>> {
+        // Find the weakly connected components of the graph, following the
+        // edges in both directions
+        //
+        // # Returns
+        // A vector with the sorted nodes of each component, ordered by their
+        // first node
+        let mut component = vec![usize::MAX; self.nodes.len()];
+        let mut components = Vec::new();
+
+        for first in 0..self.nodes.len() {
+            if component[first] != usize::MAX {
+                continue;
+            }
+            let mut nodes = vec![first];
+            let mut stack = vec![first];
+            component[first] = components.len();
+            while let Some(v) = stack.pop() {
+                for w in self.successors(v).into_iter().chain(self.predecessors(v)) {
+                    if component[w] == usize::MAX {
+                        component[w] = components.len();
+                        nodes.push(w);
+                        stack.push(w);
+                    }
+                }
+            }
+            nodes.sort();
+            components.push(nodes);
+        }
+        components
+    }
+
+    pub fn cycles(&self, limit: usize) -> (Vec<Vec<usize>>, bool) {
+        // Find the simple cycles of the graph. Each cycle is found once, from
+        // its smallest node, by a depth-first search through larger nodes.
+        // The search can follow exponentially many paths between cycles, so
+        // it stops after `STEPS_PER_CYCLE` edges per requested cycle
+        //
+        // # Arguments
+        // * `limit` - The maximum number of cycles returned
+        //
+        // # Returns
+        // A vector with the nodes of each cycle, starting and ending with its
+        // smallest node, and whether the search stopped before it was done
+        let mut cycles = Vec::new();
+        let mut on_path = vec![false; self.nodes.len()];
+        let max_steps = limit.saturating_mul(STEPS_PER_CYCLE);
+        let mut steps = 0;
+
+        for start in 0..self.nodes.len() {
+            let mut path = vec![start];
+            let mut next = vec![0];
+            on_path[start] = true;
+
+            while let Some(&v) = path.last() {
+                let successors = self.successors(v);
+                let depth = path.len() - 1;
+                if next[depth] == successors.len() {
+                    on_path[v] = false;
+                    path.pop();
+                    next.pop();
+                    continue;
+                }
+                if steps == max_steps {
+                    return (cycles, true);
+                }
+                steps += 1;
+                let w = successors[next[depth]];
+                next[depth] += 1;
+
+                if w == start {
+                    let mut cycle = path.clone();
+                    cycle.push(start);
+                    cycles.push(cycle);
+                    if cycles.len() == limit {
+                        return (cycles, false);
+                    }
+                } else if w > start && !on_path[w] {
+                    on_path[w] = true;
+                    path.push(w);
+                    next.push(0);
+                }
             }
+        }
+        (cycles, false)
+    }
+}
 
-            Ok(nodes_in_str)
+fn find_query_node(graph: &DeBruijnGraph, kmer: Option<&str>) -> Result<usize, String> {
+    // Return the index of the k-mer a query is about, or an error if it was
+    // not given or is not in the graph
+    let kmer = kmer.ok_or("This query needs a k-mer, given with --kmer")?;
+    graph
+        .find_node(kmer)
+        .ok_or(format!("K-mer {} is not in the graph", kmer))
+}
+
+pub fn run_debruijn_query(
+    sequences: Vec<&str>,
+    k: Option<usize>,
+    rc: bool,
+    query: GraphQuery,
+    kmer: Option<&str>,
+    limit: usize,
+) -> Result<String, String> {
+    // Build the de Bruijn graph of a set of sequences and answer a query
+    // about it
+    //
+    // # Arguments
+    // * `sequences` - A vector of strings containing sequences
+    // * `k` - The length of the nodes. If None, each whole sequence is one (k+1)-mer
+    // * `rc` - A boolean indicating whether to include the reverse complement of the sequences
+    // * `query` - The query to answer
+    // * `kmer` - The k-mer the query is about, for the node queries
+    // * `limit` - The maximum number of cycles returned
+    //
+    // # Returns
+    // Result containing the answer, one line per k-mer, component or cycle,
+    // or an error message
+    let graph = DeBruijnGraph::from_edge_counts(count_edges(sequences, k, rc)?);
+    let mut output = String::new();
+
+    match query {
+        GraphQuery::Successors | GraphQuery::Predecessors => {
+            let node = find_query_node(&graph, kmer)?;
+            let neighbours = match query {
+                GraphQuery::Successors => graph.successors(node),
+                _ => graph.predecessors(node),
+            };
+            for v in neighbours {
+                output.push_str(&format!("{}\n", graph.nodes[v]));
+            }
+        }
+        GraphQuery::Degree => {
+            let nodes: Vec<usize> = match kmer {
+                Some(_) => vec![find_query_node(&graph, kmer)?],
+                None => (0..graph.nodes.len()).collect(),
+            };
+            for v in nodes {
+                output.push_str(&format!(
+                    "{}\t{}\t{}\n",
+                    graph.nodes[v],
+                    graph.in_degree(v),
+                    graph.out_degree(v)
+                ));
+            }
+        }
+        GraphQuery::Search => {
+            let pattern = kmer.ok_or("This query needs a k-mer, given with --kmer")?;
+            for (v, node) in graph.nodes.iter().enumerate() {
+                if node.contains(pattern) {
+                    output.push_str(&format!(
+                        "{}\t{}\t{}\n",
+                        node,
+                        graph.in_degree(v),
+                        graph.out_degree(v)
+                    ));
+                }
+            }
+        }
+        GraphQuery::Components => {
+            for (i, component) in graph.connected_components().iter().enumerate() {
+                let kmers: Vec<&str> = component.iter().map(|&v| graph.nodes[v].as_str()).collect();
+                output.push_str(&format!(
+                    "component_{}\t{}\t{}\n",
+                    i + 1,
+                    kmers.len(),
+                    kmers.join(",")
+                ));
+            }
+        }
+        GraphQuery::Cycles => {
+            let (cycles, truncated) = graph.cycles(limit);
+            if truncated {
+                eprintln!(
+                    "Warning: stopped after {} steps, there may be more cycles",
+                    limit.saturating_mul(STEPS_PER_CYCLE)
+                );
+            }
+            for cycle in cycles {
+                let kmers: Vec<&str> = cycle.iter().map(|&v| graph.nodes[v].as_str()).collect();
+                output.push_str(&format!("{}\n", kmers.join("->")));
+            }
         }
-        Err(e) => Err(e),
     }
+    Ok(output)
 }
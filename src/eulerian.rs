@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use crate::bidirected::BidirectedGraph;
+use crate::debruijn::count_edges;
+
+#[derive(Default)]
+pub struct Multigraph {
+    // Directed graph that may have several edges between the same two nodes.
+    // Parallel edges are stored as repeated entries in the adjacency lists
+    nodes: Vec<String>,
+    index: HashMap<String, usize>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+fn parse_label(label: &str) -> Result<&str, String> {
+    // Trim a node label read from an adjacency list and check that it is
+    // non-empty ASCII, so that walks over it can be spelled byte by byte
+    let label = label.trim();
+    if label.is_empty() || !label.is_ascii() {
+        return Err(format!("Invalid node label: \"{}\"", label));
+    }
+    Ok(label)
+}
+
+impl Multigraph {
+    fn add_node(&mut self, name: &str) -> usize {
+        // Return the index of a node, adding it to the graph if it is new
+        if let Some(&i) = self.index.get(name) {
+            return i;
+        }
+        self.nodes.push(name.to_string());
+        self.adjacency.push(Vec::new());
+        self.index.insert(name.to_string(), self.nodes.len() - 1);
+
+        self.nodes.len() - 1
+    }
+
+    pub fn add_edge(&mut self, from: &str, to: &str, count: usize) {
+        // Add `count` parallel edges from one node to another
+        //
+        // # Arguments
+        // * `from` - The label of the source node
+        // * `to` - The label of the target node
+        // * `count` - The multiplicity of the edge
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        for _ in 0..count {
+            self.adjacency[from].push(to);
+        }
+    }
+
+    pub fn nodes(&self) -> &[String] {
+        &self.nodes
+    }
+
+    pub fn targets(&self, node: usize) -> &[usize] {
+        // The targets of the edges leaving a node, once per parallel edge
+        &self.adjacency[node]
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.adjacency.iter().map(|targets| targets.len()).sum()
+    }
+
+    pub fn from_edge_counts(edges: HashMap<(String, String), usize>) -> Self {
+        // Create a graph from the edges of a de Bruijn graph and their
+        // multiplicities. Edges are added in alphabetical order so that the
+        // walks are reproducible
+        //
+        // # Arguments
+        // * `edges` - A map from each edge to the number of times it was seen
+        //
+        // # Returns
+        // A new Multigraph
+        let mut edges_sorted: Vec<((String, String), usize)> = edges.into_iter().collect();
+        edges_sorted.sort();
+
+        let mut graph = Self::default();
+        for ((from, to), count) in edges_sorted {
+            graph.add_edge(&from, &to, count);
+        }
+        graph
+    }
+
+    pub fn from_adjacency_list(input: &str) -> Result<Self, String> {
+        // Parse a graph from a text adjacency list. Each line is either
+        // `A -> B,C`, with one edge to each listed target, or an `(A, B)` tuple
+        // as written by the debruijn subcommand, optionally followed by ` xN`
+        // for an edge seen N times
+        //
+        // # Arguments
+        // * `input` - A string with one line per source node or edge
+        //
+        // # Returns
+        // A new Multigraph or an error if a line can not be parsed or a node
+        // label is empty or not ASCII
+        let mut graph = Self::default();
+
+        for line in input.lines().map(|line| line.trim()) {
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(tuple) = line.strip_prefix('(') {
+                let (tuple, count) = match tuple.rsplit_once(") x") {
+                    Some((tuple, count)) => match count.trim().parse() {
+                        Ok(count) => (tuple, count),
+                        Err(_) => return Err(format!("Invalid edge count: {}", line)),
+                    },
+                    None => match tuple.strip_suffix(')') {
+                        Some(tuple) => (tuple, 1),
+                        None => return Err(format!("Invalid adjacency list line: {}", line)),
+                    },
+                };
+                match tuple.split_once(',') {
+                    Some((from, to)) => graph.add_edge(parse_label(from)?, parse_label(to)?, count),
+                    None => return Err(format!("Invalid adjacency list line: {}", line)),
+                }
+            } else if let Some((from, targets)) = line.split_once("->") {
+                let from = parse_label(from)?;
+                for to in targets.split(',') {
+                    graph.add_edge(from, parse_label(to)?, 1);
+                }
+            } else {
+                return Err(format!("Invalid adjacency list line: {}", line));
+            }
+        }
+
+        if graph.edge_count() == 0 {
+            return Err("No edges provided".to_string());
+        }
+        Ok(graph)
+    }
+}
+
+pub fn find_start_node(graph: &Multigraph) -> Result<(usize, bool), String> {
+    // Check the balance conditions for an Eulerian walk and find where it
+    // starts. A cycle needs every node to have as many incoming as outgoing
+    // edges; a path allows one node with an extra outgoing edge (the start)
+    // and one with an extra incoming edge (the end)
+    //
+    // # Arguments
+    // * `graph` - The graph to walk
+    //
+    // # Returns
+    // The start node and whether the walk is a cycle, or an error if the
+    // graph is unbalanced
+    let mut in_degree = vec![0; graph.nodes.len()];
+    for targets in &graph.adjacency {
+        for &to in targets {
+            in_degree[to] += 1;
+        }
+    }
+
+    let mut start = None;
+    let mut end = None;
+    for (v, targets) in graph.adjacency.iter().enumerate() {
+        let (count_in, count_out) = (in_degree[v], targets.len());
+        if count_out == count_in + 1 && start.is_none() {
+            start = Some(v);
+        } else if count_in == count_out + 1 && end.is_none() {
+            end = Some(v);
+        } else if count_in != count_out {
+            return Err(format!(
+                "Graph is unbalanced at node {} (in: {}, out: {})",
+                graph.nodes[v], count_in, count_out
+            ));
+        }
+    }
+
+    match (start, end) {
+        (Some(start), Some(_)) => Ok((start, false)),
+        (None, None) => {
+            let start = graph
+                .adjacency
+                .iter()
+                .position(|targets| !targets.is_empty())
+                .ok_or("No edges provided")?;
+            Ok((start, true))
+        }
+        _ => unreachable!("the in and out degrees of a graph add up to the same total"),
+    }
+}
+
+pub fn find_eulerian_walk(graph: &Multigraph) -> Result<(Vec<&str>, bool), String> {
+    // Find a walk that uses every edge of the graph exactly once, following
+    // Hierholzer's algorithm
+    //
+    // # Arguments
+    // * `graph` - The graph to walk
+    //
+    // # Returns
+    // The labels of the nodes visited, in order, and whether the walk is a
+    // cycle (its first and last nodes are the same). Fails if the graph is
+    // unbalanced or its edges are not connected
+    let (start, is_cycle) = find_start_node(graph)?;
+
+    let mut next_edge = vec![0; graph.nodes.len()];
+    let mut stack = vec![start];
+    let mut walk = Vec::with_capacity(graph.edge_count() + 1);
+
+    while let Some(&v) = stack.last() {
+        if next_edge[v] < graph.adjacency[v].len() {
+            stack.push(graph.adjacency[v][next_edge[v]]);
+            next_edge[v] += 1;
+        } else {
+            walk.push(stack.pop().unwrap());
+        }
+    }
+    walk.reverse();
+
+    if walk.len() != graph.edge_count() + 1 {
+        return Err(format!(
+            "Graph is not connected: the walk covers {} of {} edges",
+            walk.len() - 1,
+            graph.edge_count()
+        ));
+    }
+
+    Ok((
+        walk.into_iter().map(|v| graph.nodes[v].as_str()).collect(),
+        is_cycle,
+    ))
+}
+
+pub fn spell_walk(walk: &[&str]) -> Result<String, String> {
+    // Spell the string of a walk over k-mers, where each node overlaps the
+    // previous one by all but its last character
+    //
+    // # Arguments
+    // * `walk` - The labels of the nodes visited, in order
+    //
+    // # Returns
+    // The spelled string or an error if two consecutive nodes do not overlap
+    let mut spelled = walk.first().ok_or("Empty walk")?.to_string();
+
+    for pair in walk.windows(2) {
+        let (prev, next) = (pair[0], pair[1]);
+        if next.len() != prev.len() || next.is_empty() || prev[1..] != next[..next.len() - 1] {
+            return Err(format!("Nodes {} and {} do not overlap", prev, next));
+        }
+        spelled.push_str(&next[next.len() - 1..]);
+    }
+    Ok(spelled)
+}
+
+pub fn run_eulerian(
+    input: &str,
+    k: Option<usize>,
+    rc: bool,
+    path: bool,
+    circular: bool,
+) -> Result<String, String> {
+    // Find an Eulerian path or cycle of a graph and spell its string
+    //
+    // # Arguments
+    // * `input` - An adjacency list, or one read per line if `k` is given
+    // * `k` - The length of the nodes of the de Bruijn graph built from the reads
+    // * `rc` - Build the graph from both strands of the reads, walking each
+    //          edge on either strand
+    // * `path` - Return the nodes of the walk instead of the spelled string
+    // * `circular` - For cycles, drop the copy of the start node that the
+    //                spelled string ends with
+    //
+    // # Returns
+    // Result containing the walk or its string, or an error message
+    let multigraph;
+    let bidirected;
+    let (walk, is_cycle) = match k {
+        Some(k) => {
+            let sequences = input
+                .lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty())
+                .collect();
+            let edges = count_edges(sequences, Some(k), rc)?;
+            if rc {
+                bidirected = BidirectedGraph::from_edge_counts(edges)?;
+                bidirected.find_eulerian_walk()?
+            } else {
+                multigraph = Multigraph::from_edge_counts(edges);
+                find_eulerian_walk(&multigraph)?
+            }
+        }
+        None => {
+            multigraph = Multigraph::from_adjacency_list(input)?;
+            find_eulerian_walk(&multigraph)?
+        }
+    };
+
+    if path {
+        return Ok(walk.join("->"));
+    }
+
+    let spelled = spell_walk(&walk)?;
+    if circular && is_cycle {
+        return Ok(spelled[..spelled.len() - walk[0].len()].to_string());
+    }
+    Ok(spelled)
+}
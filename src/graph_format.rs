@@ -0,0 +1,37 @@
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum GraphFormat {
+    /// Plain text adjacency list
+    Text,
+    /// GFA 1.0, as read by Bandage and other graph viewers
+    Gfa,
+    /// DOT, as read by Graphviz
+    Dot,
+}
+
+pub fn check_gfa_name(name: &str) -> Result<(), String> {
+    // Check that a sequence ID or k-mer can be written as a GFA 1.0 segment
+    // name: printable ASCII without whitespace, not starting with `*` or `=`
+    // and without the `+,` and `-,` separators of GFA paths
+    //
+    // # Arguments
+    // * `name` - The name of the segment
+    //
+    // # Returns
+    // An empty Ok() or an error if the name is not a valid segment name
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_graphic())
+        && !name.starts_with(['*', '='])
+        && !name.contains("+,")
+        && !name.contains("-,");
+    if !valid {
+        return Err(format!("Invalid GFA segment name: \"{}\"", name));
+    }
+    Ok(())
+}
+
+pub fn dot_quote(name: &str) -> String {
+    // Quote a name as a DOT ID, escaping its backslashes and double quotes
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -1,9 +1,15 @@
 use std::collections::HashMap;
 
-fn populate_kmers(depth: usize, base: String, kmers: &mut Vec<String>, k: usize) {
-    // Use recursion to generate all possible DNA kmers of length k
-    // and store them in a vector. The vector is passed and modified
-    // by reference
+pub fn populate_kmers(
+    depth: usize,
+    base: String,
+    kmers: &mut Vec<String>,
+    k: usize,
+    alphabet: &[char],
+) {
+    // Use recursion to generate all possible kmers of length k over an
+    // alphabet, in lexicographic order of the alphabet, and store them in a
+    // vector. The vector is passed and modified by reference
     //
     // # Arguments
     //
@@ -11,17 +17,17 @@ fn populate_kmers(depth: usize, base: String, kmers: &mut Vec<String>, k: usize)
     // * `base` - the current base string
     // * `kmers` - the vector that will store the kmers
     // * `k` - the length of the kmers
+    // * `alphabet` - the symbols the kmers are made of
     //
     // # Modifies
     // * `kmers` - the vector that will store the kmers
     if depth == k {
         kmers.push(base);
     } else {
-        let bases = vec!["A", "C", "G", "T"];
-        for &b in &bases {
+        for &b in alphabet {
             let mut new_base = base.clone();
-            new_base.push_str(b);
-            populate_kmers(depth + 1, new_base, kmers, k);
+            new_base.push(b);
+            populate_kmers(depth + 1, new_base, kmers, k, alphabet);
         }
     }
 }
@@ -114,7 +120,7 @@ fn generate_result_string(ordered_counts: Vec<(String, i32)>) -> String {
 
 pub fn run_kmers(sequence: String, k: usize) -> Result<String, String> {
     let mut kmers: Vec<String> = vec![];
-    populate_kmers(0, "".to_string(), &mut kmers, k);
+    populate_kmers(0, "".to_string(), &mut kmers, k, &['A', 'C', 'G', 'T']);
 
     let mut kmer_map = create_kmer_map(kmers);
 
@@ -1,14 +1,26 @@
 use std::io::Read;
+use std::path::PathBuf;
 
 use atty::Stream;
 use clap::Parser;
 
+mod bidirected;
 mod cli;
+mod colored;
+mod contigs;
 mod debruijn;
+mod eulerian;
+mod graph_format;
 mod kmer;
+mod nucleotide;
 mod overlap;
+mod paired;
 mod perfect_assembly;
+mod simplify;
+mod string_graph;
 mod superstring;
+mod unitig;
+mod universal;
 mod assembly_quality;
 
 #[derive(Debug)]
@@ -79,6 +91,116 @@ fn read_mulitfasta(fasta: String) -> Vec<Fasta> {
     fastas
 }
 
+#[derive(Debug)]
+pub struct Fastq {
+    // Struct to hold a fastq record
+    header: String,
+    seq: String,
+    qual: String,
+}
+
+fn read_fastq(fastq: &str) -> Result<Vec<Fastq>, String> {
+    // Create a vector of Fastq structs from a fastq string, with four lines
+    // per record. Blank lines are only skipped before the first record and
+    // after the last one, since an empty sequence and its quality are blank
+    //
+    // # Arguments
+    // * `fastq` - A string containing fastq records
+    //
+    // # Returns
+    // A vector of Fastq structs or an error if a record is malformed
+    let mut records = Vec::new();
+    let lines: Vec<&str> = fastq.lines().map(|line| line.trim()).collect();
+    let first = lines.iter().position(|line| !line.is_empty()).unwrap_or(0);
+    let last = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(0, |i| i + 1);
+
+    for record in lines[first..last].chunks(4) {
+        let header = record[0]
+            .strip_prefix('@')
+            .ok_or(format!("Invalid fastq header: {}", record[0]))?;
+        let (seq, separator, qual) = match record {
+            [_, seq, separator, qual] => (*seq, *separator, *qual),
+            _ => return Err(format!("Incomplete fastq record: {}", header)),
+        };
+        if !separator.starts_with('+') {
+            return Err(format!("Invalid fastq separator in record {}", header));
+        }
+        if seq.len() != qual.len() {
+            return Err(format!(
+                "Sequence and quality lengths differ in record {} ({} != {})",
+                header,
+                seq.len(),
+                qual.len()
+            ));
+        }
+        records.push(Fastq {
+            header: header.to_string(),
+            seq: seq.to_string(),
+            qual: qual.to_string(),
+        });
+    }
+    Ok(records)
+}
+
+fn read_sequences(input: String) -> Vec<String> {
+    // Read the sequences of a multi-fasta string, or one sequence per line if
+    // the input is not in fasta format
+    //
+    // # Arguments
+    // * `input` - A string containing the sequences
+    //
+    // # Returns
+    // A vector with the non-empty sequences
+    if input.trim_start().starts_with('>') {
+        return read_mulitfasta(input)
+            .into_iter()
+            .map(|fasta| fasta.seq.trim().to_string())
+            .collect();
+    }
+    read_lines(&input).into_iter().map(String::from).collect()
+}
+
+fn read_lines(input: &str) -> Vec<&str> {
+    // Split an input into its lines, trimmed, skipping the empty ones
+    //
+    // # Arguments
+    // * `input` - A string with one sequence per line
+    //
+    // # Returns
+    // A vector with the non-empty lines
+    input
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+fn read_input(input: Option<PathBuf>) -> String {
+    // Read the input from a file, or from stdin if no path is given
+    //
+    // # Arguments
+    // * `input` - The path to the input file
+    //
+    // # Returns
+    // The contents of the input. Exits if no input is provided
+    match input {
+        Some(path) => std::fs::read_to_string(path).unwrap(),
+        None => {
+            if atty::is(Stream::Stdin) {
+                eprintln!("Error: No input provided");
+                std::process::exit(1);
+            }
+            let mut buffer = String::new();
+            std::io::stdin().read_to_string(&mut buffer).unwrap();
+
+            buffer
+        }
+    }
+}
+
 fn main() {
     // Get the command line arguments
     let args = cli::Cli::parse();
@@ -117,7 +239,21 @@ fn main() {
                 }
             }
         }
-        cli::Commands::Overlap { input, k } => {
+        cli::Commands::Overlap {
+            input,
+            k,
+            max_errors,
+            max_error_rate,
+            rc,
+            format,
+            collapse_labels,
+            paf,
+        } => {
+            cli::check_collapse_labels(
+                "overlap",
+                collapse_labels,
+                format == graph_format::GraphFormat::Dot,
+            );
             let input = match input.to_str() {
                 Some("-") => {
                     let mut buffer = String::new();
@@ -133,7 +269,13 @@ fn main() {
 
             let fastas = read_mulitfasta(input);
 
-            let result = overlap::run_overlap_graph(fastas, k);
+            let options = overlap::OverlapOptions {
+                k,
+                max_errors,
+                max_error_rate,
+                rc,
+            };
+            let result = overlap::run_overlap_graph(fastas, options, format, collapse_labels, paf);
             match result {
                 Ok(result) => {
                     println!("{}", result);
@@ -145,27 +287,113 @@ fn main() {
                 }
             }
         }
-        cli::Commands::Superstring { input } => {
-            let input = match input {
-                Some(path) => std::fs::read_to_string(path).unwrap(),
-                None => {
-                    if atty::is(Stream::Stdin) {
-                        eprintln!("Error: No input provided");
+        cli::Commands::StringGraph {
+            input,
+            k,
+            max_errors,
+            max_error_rate,
+            rc,
+            fuzz,
+            format,
+            collapse_labels,
+            paf,
+        } => {
+            cli::check_collapse_labels(
+                "string-graph",
+                collapse_labels,
+                format == graph_format::GraphFormat::Dot,
+            );
+            let input = read_input(input);
+
+            let fastas = read_mulitfasta(input);
+
+            let options = overlap::OverlapOptions {
+                k,
+                max_errors,
+                max_error_rate,
+                rc,
+            };
+            let result =
+                string_graph::run_string_graph(fastas, options, fuzz, format, collapse_labels, paf);
+            match result {
+                Ok(result) => {
+                    println!("{}", result);
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        cli::Commands::Superstring {
+            input,
+            layout,
+            layout_format,
+            min_overlap,
+            all,
+            limit,
+        } => {
+            let input = read_input(input);
+
+            let fastas = read_mulitfasta(input);
+
+            if all {
+                match superstring::run_all_superstrings(fastas, limit) {
+                    Ok(result) => {
+                        println!("{}", result);
+                        std::process::exit(0);
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e);
                         std::process::exit(1);
                     }
-                    let mut buffer = String::new();
-                    std::io::stdin().read_to_string(&mut buffer).unwrap();
+                }
+            }
 
-                    buffer
+            let result = superstring::run_superstring(
+                fastas,
+                layout.as_ref().map(|_| layout_format),
+                min_overlap,
+            );
+            match result {
+                Ok((result, layout_report)) => {
+                    if let (Some(path), Some(report)) = (layout, layout_report) {
+                        if let Err(e) = std::fs::write(&path, report) {
+                            eprintln!("Error writing {}: {}", path.display(), e);
+                            std::process::exit(1);
+                        }
+                    }
+                    println!("{}", result);
+                    std::process::exit(0);
                 }
-            };
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        cli::Commands::Debruijn {
+            command:
+                Some(cli::DebruijnCommand::Query {
+                    query,
+                    input,
+                    k,
+                    rc,
+                    kmer,
+                    limit,
+                }),
+            ..
+        } => {
+            let input = read_input(input);
 
-            let fastas = read_mulitfasta(input);
+            let sequences = read_lines(&input);
 
-            let result = superstring::run_superstring(fastas);
+            let result =
+                debruijn::run_debruijn_query(sequences, k, rc, query, kmer.as_deref(), limit);
             match result {
                 Ok(result) => {
-                    println!("{}", result);
+                    print!("{}", result);
                     std::process::exit(0);
                 }
                 Err(e) => {
@@ -175,30 +403,84 @@ fn main() {
             }
         }
         cli::Commands::Debruijn {
+            command: None,
             input,
-            rc
+            k,
+            rc,
+            format,
+            collapse_labels,
+            counts,
+            simplify,
         } => {
-            let input = match input {
-                Some(path) => std::fs::read_to_string(path).unwrap(),
-                None => {
-                    if atty::is(Stream::Stdin) {
-                        eprintln!("Error: No input provided");
-                        std::process::exit(1);
-                    }
-                    let mut buffer = String::new();
-                    std::io::stdin().read_to_string(&mut buffer).unwrap();
+            cli::check_collapse_labels(
+                "debruijn",
+                collapse_labels,
+                format == graph_format::GraphFormat::Dot,
+            );
+            let input = read_input(input);
 
-                    buffer
+            let sequences = read_lines(&input);
+
+            let result = debruijn::run_debruijn_graph(
+                sequences,
+                k,
+                rc,
+                format,
+                collapse_labels,
+                counts,
+                &simplify,
+            );
+            match result {
+                Ok(result) => {
+                    print!("{}", result);
+                    std::process::exit(0);
                 }
-            };
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        cli::Commands::PairedDebruijn {
+            input,
+            d,
+            adjacency,
+            spell,
+        } => {
+            let input = read_input(input);
 
-            let mut sequences = input.split('\n').collect::<Vec<&str>>();
-            for seq in &mut sequences {
-                *seq = seq.trim();
+            let result = paired::run_paired_debruijn(&input, d, adjacency, spell);
+            match result {
+                Ok(result) => {
+                    if adjacency {
+                        print!("{}", result);
+                    } else {
+                        println!("{}", result);
+                    }
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
             }
-            sequences = sequences.into_iter().filter(|s| !s.is_empty()).collect();
+        }
+        cli::Commands::Contigs {
+            input,
+            k,
+            rc,
+            simplify,
+        } => {
+            let input = read_input(input);
 
-            let result = debruijn::run_debruijn_graph(sequences, rc);
+            let sequences = read_sequences(input);
+
+            let result = contigs::run_contigs(
+                sequences.iter().map(|s| s.as_str()).collect(),
+                k,
+                rc,
+                &simplify,
+            );
             match result {
                 Ok(result) => {
                     print!("{}", result);
@@ -210,28 +492,74 @@ fn main() {
                 }
             }
         }
-        cli::Commands::PerfectAssembly { input } => {
-            let input = match input {
-                Some(path) => std::fs::read_to_string(path).unwrap(),
-                None => {
-                    if atty::is(Stream::Stdin) {
-                        eprintln!("Error: No input provided");
+        cli::Commands::Colored {
+            inputs,
+            k,
+            by_record,
+            rc,
+            query,
+        } => {
+            let mut samples = Vec::new();
+            for path in inputs {
+                let input = match std::fs::read_to_string(&path) {
+                    Ok(input) => input,
+                    Err(e) => {
+                        eprintln!("Error reading {}: {}", path.display(), e);
                         std::process::exit(1);
                     }
-                    let mut buffer = String::new();
-                    std::io::stdin().read_to_string(&mut buffer).unwrap();
+                };
+                if by_record {
+                    samples.extend(read_mulitfasta(input).into_iter().map(|fasta| {
+                        colored::Sample {
+                            name: fasta.header,
+                            sequences: vec![fasta.seq],
+                        }
+                    }));
+                } else {
+                    samples.push(colored::Sample {
+                        name: path.display().to_string(),
+                        sequences: read_sequences(input),
+                    });
+                }
+            }
 
-                    buffer
+            let result = colored::run_colored_graph(samples, k, rc, query);
+            match result {
+                Ok(result) => {
+                    print!("{}", result);
+                    std::process::exit(0);
                 }
-            };
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        cli::Commands::Revcomp { input, mode } => {
+            let input = read_input(input);
 
-            let mut sequences = input.split('\n').collect::<Vec<&str>>();
-            for seq in &mut sequences {
-                *seq = seq.trim();
+            let result = nucleotide::run_revcomp(input, mode);
+            match result {
+                Ok(result) => {
+                    print!("{}", result);
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
             }
-            sequences = sequences.into_iter().filter(|s| !s.is_empty()).collect();
+        }
+        cli::Commands::Eulerian {
+            input,
+            k,
+            rc,
+            path,
+            circular,
+        } => {
+            let input = read_input(input);
 
-            let result = perfect_assembly::run_perfect_assembly(sequences);
+            let result = eulerian::run_eulerian(&input, k, rc, path, circular);
             match result {
                 Ok(result) => {
                     println!("{}", result);
@@ -243,26 +571,44 @@ fn main() {
                 }
             }
         }
-        cli::Commands::AssemblyQuality { input } => {
-            let input = match input {
-                Some(path) => std::fs::read_to_string(path).unwrap(),
-                None => {
-                    if atty::is(Stream::Stdin) {
-                        eprintln!("Error: No input provided");
-                        std::process::exit(1);
-                    }
-                    let mut buffer = String::new();
-                    std::io::stdin().read_to_string(&mut buffer).unwrap();
-
-                    buffer
+        cli::Commands::Universal {
+            k,
+            alphabet,
+            linear,
+        } => {
+            let result = universal::run_universal(k, &alphabet, linear);
+            match result {
+                Ok(result) => {
+                    println!("{}", result);
+                    std::process::exit(0);
                 }
-            };
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        cli::Commands::PerfectAssembly { input, rc } => {
+            let input = read_input(input);
+
+            let sequences = read_lines(&input);
 
-            let mut sequences = input.split('\n').collect::<Vec<&str>>();
-            for seq in &mut sequences {
-                *seq = seq.trim();
+            let result = perfect_assembly::run_perfect_assembly(sequences, rc);
+            match result {
+                Ok(result) => {
+                    println!("{}", result);
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
             }
-            sequences = sequences.into_iter().filter(|s| !s.is_empty()).collect();
+        }
+        cli::Commands::AssemblyQuality { input } => {
+            let input = read_input(input);
+
+            let sequences = read_lines(&input);
 
             let result = assembly_quality::run_assembly_quality(sequences);
             println!("{}", result);
@@ -274,6 +620,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use graph_format::GraphFormat;
 
     #[test]
     fn test_kmer() {
@@ -299,4 +646,919 @@ mod tests {
 
         assert_eq!(kmer::run_kmers(seq, k).unwrap(), expected);
     }
+
+    #[test]
+    fn test_overlap_with_errors() {
+        let fastas = vec![
+            Fasta::new(String::from("a"), String::from("GGGGACGTACGTTT")),
+            Fasta::new(String::from("b"), String::from("ACGAACGTTTCCCC")),
+        ];
+        let options = overlap::OverlapOptions {
+            k: 5,
+            max_errors: Some(1),
+            max_error_rate: None,
+            rc: false,
+        };
+
+        assert_eq!(
+            overlap::run_overlap_graph(fastas, options, GraphFormat::Text, false, false).unwrap(),
+            "a b 10 0.900 10M"
+        );
+
+        // An exact overlap is reported as such when errors are allowed, rather
+        // than lengthened with indels at its ends
+        let overlap = |input: &str, max_errors, max_error_rate| {
+            let options = overlap::OverlapOptions {
+                k: 5,
+                max_errors,
+                max_error_rate,
+                rc: false,
+            };
+            let fastas = read_mulitfasta(String::from(input));
+            overlap::run_overlap_graph(fastas, options, GraphFormat::Text, false, false).unwrap()
+        };
+        assert_eq!(
+            overlap(">a\nGGGGACGTACGTTT\n>b\nACGTACGTTTCCCC\n", Some(1), None),
+            "a b 10 1.000 10M"
+        );
+        let mut state: u32 = 11;
+        let genome: String = (0..150)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                ['A', 'C', 'G', 'T'][(state >> 16) as usize % 4]
+            })
+            .collect();
+        let input = format!(">a\n{}\n>b\n{}\n", &genome[..100], &genome[50..]);
+        assert_eq!(overlap(&input, None, Some(0.1)), "a b 50 1.000 50M");
+    }
+
+    #[test]
+    fn test_overlap_rejects_invalid_records() {
+        let options = || overlap::OverlapOptions {
+            k: 3,
+            max_errors: None,
+            max_error_rate: None,
+            rc: false,
+        };
+
+        let duplicated = read_mulitfasta(String::from(">a\nAAATTT\n>a\nTTTCCC\n"));
+        assert_eq!(
+            overlap::run_overlap_graph(duplicated, options(), GraphFormat::Text, false, false),
+            Err(String::from("Duplicate sequence ID: a"))
+        );
+
+        let short = read_mulitfasta(String::from(">a\nAAATTT\n>b\nTT\n"));
+        assert_eq!(
+            overlap::run_overlap_graph(short, options(), GraphFormat::Text, false, false),
+            Err(String::from("Sequence b is shorter than k (2 < 3)"))
+        );
+    }
+
+    #[test]
+    fn test_overlap_orientations() {
+        // Each read overlaps `a` in a single orientation, and its mirror on
+        // the other strand is not reported again
+        let fastas = read_mulitfasta(String::from(
+            ">a\nGGATCCAT\n>b\nCCATGAAG\n>c\nCGAAATGG\n>d\nATCCTTGA\n>e\nCTTTGGAT\n",
+        ));
+        let options = overlap::OverlapOptions {
+            k: 4,
+            max_errors: None,
+            max_error_rate: None,
+            rc: true,
+        };
+
+        assert_eq!(
+            overlap::run_overlap_graph(fastas, options, GraphFormat::Text, false, false).unwrap(),
+            "a b +/+\na c +/-\na d -/+\na e -/-"
+        );
+    }
+
+    #[test]
+    fn test_overlap_gfa() {
+        let options = || overlap::OverlapOptions {
+            k: 4,
+            max_errors: None,
+            max_error_rate: None,
+            rc: true,
+        };
+
+        let fastas = read_mulitfasta(String::from(">a\nGGATCCAT\n>b\nCCATGAAG\n>c\nCGAAATGG\n"));
+        assert_eq!(
+            overlap::run_overlap_graph(fastas, options(), GraphFormat::Gfa, false, false).unwrap(),
+            "H\tVN:Z:1.0\n\
+             S\ta\tGGATCCAT\n\
+             S\tb\tCCATGAAG\n\
+             S\tc\tCGAAATGG\n\
+             L\ta\t+\tb\t+\t4M\n\
+             L\ta\t+\tc\t-\t4M"
+        );
+
+        // Segment names can't have whitespace
+        let fastas = read_mulitfasta(String::from(">a x\nGGATCCAT\n>b\nCCATGAAG\n"));
+        assert_eq!(
+            overlap::run_overlap_graph(fastas, options(), GraphFormat::Gfa, false, false),
+            Err(String::from("Invalid GFA segment name: \"a x\""))
+        );
+    }
+
+    #[test]
+    fn test_overlap_paf() {
+        // The target has an extra base, which is a deletion in PAF, where the
+        // target is the reference
+        let options = || overlap::OverlapOptions {
+            k: 8,
+            max_errors: Some(1),
+            max_error_rate: None,
+            rc: true,
+        };
+        let paf = |input: &str| {
+            overlap::run_overlap_graph(
+                read_mulitfasta(String::from(input)),
+                options(),
+                GraphFormat::Text,
+                false,
+                true,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            paf(">a\nGGGGACGTACGTTT\n>b\nACGTAACGTTTCCCC\n"),
+            "a\t14\t4\t14\t+\tb\t15\t0\t11\t10\t11\t255\tcg:Z:4M1D6M"
+        );
+        // On the reverse strand the alignment is read along the forward target
+        assert_eq!(
+            paf(">a\nGGGGACGTACGTTT\n>c\nGGGGAAACGTTACGT\n"),
+            "a\t14\t4\t14\t-\tc\t15\t4\t15\t10\t11\t255\tcg:Z:6M1D4M"
+        );
+        // An extra base in the query is an insertion, and only the aligned
+        // bases count in the spans
+        assert_eq!(
+            paf(">c\nGGGGACGTAACGTTT\n>b\nACGTACGTTTCCCC\n"),
+            "c\t15\t4\t15\t+\tb\t14\t0\t10\t10\t11\t255\tcg:Z:4M1I6M"
+        );
+        // An exact overlap keeps exact coordinates when errors are allowed
+        assert_eq!(
+            paf(">a\nGGGGACGTACGTTT\n>b\nACGTACGTTTCCCC\n"),
+            "a\t14\t4\t14\t+\tb\t14\t0\t10\t10\t10\t255\tcg:Z:10M"
+        );
+    }
+
+    #[test]
+    fn test_dot_output() {
+        let fastas = read_mulitfasta(String::from(
+            ">a \"x\"\nGGATCCAT\n>b\nCCATGAAG\n>c\nCGAAATGG\n",
+        ));
+        let options = overlap::OverlapOptions {
+            k: 4,
+            max_errors: None,
+            max_error_rate: None,
+            rc: true,
+        };
+        assert_eq!(
+            overlap::run_overlap_graph(fastas, options, GraphFormat::Dot, false, false).unwrap(),
+            "digraph overlap {\n    \
+             \"a \\\"x\\\"\";\n    \
+             \"b\";\n    \
+             \"c\";\n    \
+             \"a \\\"x\\\"\" -> \"b\" [label=\"4\"];\n    \
+             \"a \\\"x\\\"\" -> \"c\" [label=\"4 +/-\"];\n\
+             }"
+        );
+
+        let simplify = cli::SimplifyArgs {
+            min_coverage: None,
+            tip_length: None,
+            bubble_length: None,
+        };
+        assert_eq!(
+            debruijn::run_debruijn_graph(
+                vec!["ACGTT", "CGTA"],
+                Some(2),
+                false,
+                GraphFormat::Dot,
+                true,
+                true,
+                &simplify
+            )
+            .unwrap(),
+            "digraph debruijn {\n    \
+             node [shape=point, label=\"\"];\n    \
+             \"AC\" -> \"CG\" [label=\"ACG x1\"];\n    \
+             \"CG\" -> \"GT\" [label=\"CGT x2\"];\n    \
+             \"GT\" -> \"TA\" [label=\"GTA x1\"];\n    \
+             \"GT\" -> \"TT\" [label=\"GTT x1\"];\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_debruijn_k() {
+        let simplify = cli::SimplifyArgs {
+            min_coverage: None,
+            tip_length: None,
+            bubble_length: None,
+        };
+        let debruijn = |k, format| {
+            debruijn::run_debruijn_graph(
+                vec!["ACGTACGTTT", "CGTACG"],
+                Some(k),
+                false,
+                format,
+                false,
+                false,
+                &simplify,
+            )
+        };
+
+        assert_eq!(
+            debruijn(3, GraphFormat::Text).unwrap(),
+            "(ACG, CGT)\n(CGT, GTA)\n(CGT, GTT)\n(GTA, TAC)\n(GTT, TTT)\n(TAC, ACG)\n"
+        );
+        assert_eq!(
+            debruijn(3, GraphFormat::Gfa).unwrap(),
+            "H\tVN:Z:1.0\n\
+             S\tACG\tACG\tKC:i:2\n\
+             S\tCGT\tCGT\tKC:i:3\n\
+             S\tGTA\tGTA\tKC:i:2\n\
+             S\tGTT\tGTT\tKC:i:1\n\
+             S\tTAC\tTAC\tKC:i:2\n\
+             S\tTTT\tTTT\tKC:i:1\n\
+             L\tACG\t+\tCGT\t+\t2M\tEC:i:2\n\
+             L\tCGT\t+\tGTA\t+\t2M\tEC:i:2\n\
+             L\tCGT\t+\tGTT\t+\t2M\tEC:i:1\n\
+             L\tGTA\t+\tTAC\t+\t2M\tEC:i:2\n\
+             L\tGTT\t+\tTTT\t+\t2M\tEC:i:1\n\
+             L\tTAC\t+\tACG\t+\t2M\tEC:i:2\n"
+        );
+        assert_eq!(
+            debruijn(0, GraphFormat::Text),
+            Err(String::from("The length of the nodes must be at least 1"))
+        );
+        assert_eq!(
+            debruijn(7, GraphFormat::Text),
+            Err(String::from("Sequence CGTACG is shorter than k+1 (6 < 8)"))
+        );
+        assert_eq!(
+            debruijn::count_edges(vec!["A\u{e9}CGT"], Some(2), true),
+            Err(String::from("Invalid sequence: \"A\u{e9}CGT\""))
+        );
+    }
+
+    #[test]
+    fn test_string_graph() {
+        // d is contained in a and b, and the overlap of a and c is inferred
+        // from those of a and b and of b and c
+        let fastas = read_mulitfasta(String::from(
+            ">a\nTTAGTTGTGCCGCAGCGAAG\n>b\nCGCAGCGAAGTAGTG\n\
+             >c\nGAAGTAGTGCTTGAAATATGCGAC\n>d\nCAGCGA\n",
+        ));
+        let options = overlap::OverlapOptions {
+            k: 3,
+            max_errors: None,
+            max_error_rate: None,
+            rc: false,
+        };
+
+        assert_eq!(
+            string_graph::run_string_graph(fastas, options, 10, GraphFormat::Text, false, false)
+                .unwrap(),
+            "a b 10 1.000 10M\nb c 9 1.000 9M"
+        );
+
+        // The reverse complement of b overlaps a by 12 bases, and b also
+        // starts with the last 4 bases of a by chance
+        let fastas = read_mulitfasta(String::from(
+            ">a\nGGATCACAGTCTACACTGCT\n>b\nTGCTCACTCCAAAGCAGTGTAGAC\n",
+        ));
+        let options = overlap::OverlapOptions {
+            k: 4,
+            max_errors: None,
+            max_error_rate: None,
+            rc: true,
+        };
+        assert_eq!(
+            string_graph::run_string_graph(fastas, options, 0, GraphFormat::Text, false, false)
+                .unwrap(),
+            "a b +/- 12 1.000 12M"
+        );
+    }
+
+    #[test]
+    fn test_superstring() {
+        let fastas = read_mulitfasta(String::from(
+            ">a\nATTAGACCTG\n>b\nCCTGCCGGAA\n>c\nAGACCTGCCG\n>d\nGCCGGAATAC\n",
+        ));
+        let (result, layout) =
+            superstring::run_superstring(fastas, Some(superstring::LayoutFormat::Tsv), None)
+                .unwrap();
+
+        assert_eq!(result, "ATTAGACCTGCCGGAATAC");
+        assert_eq!(
+            layout.unwrap(),
+            "id\tcontig\tstart\tend\toverlap\n\
+             a\tsuperstring\t0\t10\t0\n\
+             c\tsuperstring\t3\t13\t7\n\
+             b\tsuperstring\t6\t16\t7\n\
+             d\tsuperstring\t9\t19\t7\n"
+        );
+
+        // Merging the largest overlaps first joins c, d, b and a into 12 bases,
+        // while the shortest superstring has 11
+        let input = ">a\nCACAA\n>b\nAACAC\n>c\nCACCC\n>d\nACCCA\n";
+        let greedy =
+            superstring::run_superstring(read_mulitfasta(String::from(input)), None, Some(1))
+                .unwrap()
+                .0;
+        assert_eq!(greedy, ">contig_1 len=12 reads=c,d,b,a\nCACCCAACACAA");
+        let (result, _) =
+            superstring::run_superstring(read_mulitfasta(String::from(input)), None, None).unwrap();
+        assert_eq!(result, "CACAACACCCA");
+
+        let non_ascii = read_mulitfasta(String::from(">a\nA\u{e9}\n>b\n\u{e9}C\n"));
+        assert_eq!(
+            superstring::run_superstring(non_ascii, None, None).err(),
+            Some("Error: sequences can only contain ASCII characters")
+        );
+    }
+
+    #[test]
+    fn test_superstring_ties() {
+        // The reads don't overlap, so both orders are optimal and the input
+        // order decides
+        let superstring = |input: &str| {
+            superstring::run_superstring(read_mulitfasta(String::from(input)), None, None)
+                .unwrap()
+                .0
+        };
+        assert_eq!(superstring(">a\nAAA\n>b\nCCC\n"), "AAACCC");
+        assert_eq!(superstring(">b\nCCC\n>a\nAAA\n"), "CCCAAA");
+
+        let all = |input: &str, limit| {
+            superstring::run_all_superstrings(read_mulitfasta(String::from(input)), limit).unwrap()
+        };
+        assert_eq!(all(">a\nAAA\n>b\nCCC\n", 100), "AAACCC\nCCCAAA");
+        assert_eq!(all(">b\nCCC\n>a\nAAA\n", 1), "CCCAAA");
+
+        // In the greedy merge, x overlaps y and z by two bases, and the read
+        // that comes first in the input is merged
+        let contigs = |input: &str| {
+            superstring::run_superstring(read_mulitfasta(String::from(input)), None, Some(2))
+                .unwrap()
+                .0
+        };
+        assert_eq!(
+            contigs(">x\nAAAC\n>y\nACGG\n>z\nACTT\n"),
+            ">contig_1 len=6 reads=x,y\nAAACGG\n>contig_2 len=4 reads=z\nACTT"
+        );
+        assert_eq!(
+            contigs(">x\nAAAC\n>z\nACTT\n>y\nACGG\n"),
+            ">contig_1 len=6 reads=x,z\nAAACTT\n>contig_2 len=4 reads=y\nACGG"
+        );
+    }
+
+    #[test]
+    fn test_superstring_contigs() {
+        // Two chains of reads that don't overlap each other, and a read
+        // contained in b
+        let fastas = read_mulitfasta(String::from(
+            ">a\nATTAGACC\n>d\nTTTTCAAA\n>b\nGACCTGCC\n\
+             >f\nACCTG\n>e\nCAAAGGGG\n>c\nTGCCGG\n",
+        ));
+        let (result, layout) =
+            superstring::run_superstring(fastas, Some(superstring::LayoutFormat::Tsv), Some(3))
+                .unwrap();
+
+        assert_eq!(
+            result,
+            ">contig_1 len=14 reads=a,b,f,c\nATTAGACCTGCCGG\n\
+             >contig_2 len=12 reads=d,e\nTTTTCAAAGGGG"
+        );
+        assert_eq!(
+            layout.unwrap(),
+            "id\tcontig\tstart\tend\toverlap\n\
+             a\tcontig_1\t0\t8\t0\n\
+             b\tcontig_1\t4\t12\t4\n\
+             f\tcontig_1\t5\t10\t5\n\
+             c\tcontig_1\t8\t14\t2\n\
+             d\tcontig_2\t0\t8\t0\n\
+             e\tcontig_2\t4\t12\t4\n"
+        );
+    }
+
+    #[test]
+    fn test_superstring_greedy_merge() {
+        // More reads than the exact solver takes, tiled along a pseudo-random
+        // genome, plus a read contained in another one and a duplicated read
+        let mut state: u32 = 7;
+        let genome: String = (0..200)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                ['A', 'C', 'G', 'T'][(state >> 16) as usize % 4]
+            })
+            .collect();
+        let mut input = String::new();
+        for start in (0..=180).step_by(6) {
+            input.push_str(&format!(">r{}\n{}\n", start, &genome[start..start + 20]));
+        }
+        input.push_str(&format!(">contained\n{}\n", &genome[40..50]));
+        input.push_str(&format!(">duplicate\n{}\n", &genome[60..80]));
+        let fastas = read_mulitfasta(input);
+        assert!(fastas.len() > 30);
+
+        let (result, layout) =
+            superstring::run_superstring(fastas, Some(superstring::LayoutFormat::Tsv), None)
+                .unwrap();
+        assert_eq!(result, genome);
+        let layout = layout.unwrap();
+        assert!(layout.contains("contained\tsuperstring\t40\t50\t"));
+        assert!(layout.contains("duplicate\tsuperstring\t60\t80\t"));
+    }
+
+    fn cyclic_composition(genome: &str, k: usize) -> Vec<String> {
+        // The k-mers of a circular genome, sorted, once per position
+        let wrapped = format!("{}{}", genome, &genome[..k - 1]);
+        let mut kmers: Vec<String> = (0..genome.len())
+            .map(|i| wrapped[i..i + k].to_string())
+            .collect();
+        kmers.sort();
+        kmers
+    }
+
+    #[test]
+    fn test_perfect_assembly_with_repeats() {
+        // GATT and ATTC are followed by different bases, and the 5-mers
+        // GATTC and ATTCG appear twice
+        let genome = "GATTCGAAACGATTCGTTTCGATTA";
+        let composition = cyclic_composition(genome, 5);
+        let reads: Vec<&str> = composition.iter().map(|read| read.as_str()).collect();
+
+        let result = perfect_assembly::run_perfect_assembly(reads, false).unwrap();
+        assert_eq!(result.len(), genome.len());
+        assert_eq!(cyclic_composition(&result, 5), composition);
+    }
+
+    #[test]
+    fn test_eulerian() {
+        // Path through the de Bruijn graph of the reads
+        let reads = "CTTA\nACCA\nTACC\nGGCT\nGCTT\nTTAC";
+        let result = eulerian::run_eulerian(reads, Some(3), false, false, false).unwrap();
+        assert_eq!(result, "GGCTTACCA");
+        let result = eulerian::run_eulerian(reads, Some(3), false, true, false).unwrap();
+        assert_eq!(result, "GGC->GCT->CTT->TTA->TAC->ACC->CCA");
+
+        // Cycle, which with --circular does not repeat its start node
+        let cycle = "(AC, CG)\n(CG, GT)\n(GT, TA)\n(TA, AC)";
+        let result = eulerian::run_eulerian(cycle, None, false, false, false).unwrap();
+        assert_eq!(result, "ACGTAC");
+        let result = eulerian::run_eulerian(cycle, None, false, false, true).unwrap();
+        assert_eq!(result, "ACGT");
+
+        // --circular leaves paths unchanged
+        let result =
+            eulerian::run_eulerian("AC -> CG\nCG -> GT", None, false, false, true).unwrap();
+        assert_eq!(result, "ACGT");
+
+        let result = eulerian::run_eulerian("A -> B\nA -> C", None, false, false, false);
+        assert_eq!(
+            result.unwrap_err(),
+            "Graph is unbalanced at node A (in: 0, out: 2)"
+        );
+
+        // Labels that are not ASCII can not be spelled byte by byte
+        let result = eulerian::run_eulerian("(Aé, éB)", None, false, false, false);
+        assert_eq!(result.unwrap_err(), "Invalid node label: \"Aé\"");
+    }
+
+    #[test]
+    fn test_contigs() {
+        let no_simplify = cli::SimplifyArgs {
+            min_coverage: None,
+            tip_length: None,
+            bubble_length: None,
+        };
+
+        // The two reads join at CGT and leave it on different branches
+        let reads = vec!["AAACGTT", "TCCCGTG", "AAACGTT"];
+        let edges = debruijn::count_edges(reads.clone(), Some(3), false).unwrap();
+        let graph = debruijn::DeBruijnGraph::from_edge_counts(edges);
+        let unitigs = unitig::find_unitigs(&graph);
+        let seqs: Vec<&str> = unitigs.iter().map(|unitig| unitig.seq.as_str()).collect();
+        assert_eq!(seqs, vec!["AAACGT", "CGTG", "CGTT", "TCCCGT"]);
+        assert_eq!(unitigs[0].edges.len(), 3);
+        assert_eq!(unitigs[0].coverage(&graph), 2.0);
+
+        let result = contigs::run_contigs(reads, 3, false, &no_simplify).unwrap();
+        assert_eq!(
+            result,
+            ">unitig_1 len=6 cov=2.00\nAAACGT\n\
+             >unitig_2 len=4 cov=1.00\nCGTG\n\
+             >unitig_3 len=4 cov=2.00\nCGTT\n\
+             >unitig_4 len=6 cov=1.00\nTCCCGT\n"
+        );
+
+        // A cycle without branching nodes is one unitig from its first edge
+        let edges = debruijn::count_edges(vec!["ACGTACG"], Some(3), false).unwrap();
+        let graph = debruijn::DeBruijnGraph::from_edge_counts(edges);
+        let unitigs = unitig::find_unitigs(&graph);
+        assert_eq!(unitigs.len(), 1);
+        assert_eq!(unitigs[0].seq, "ACGTACG");
+        assert_eq!(unitigs[0].edges, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_simplify() {
+        let simplify = |min_coverage, tip_length, bubble_length| cli::SimplifyArgs {
+            min_coverage,
+            tip_length,
+            bubble_length,
+        };
+
+        let reads = vec!["AAACGTT", "AAACGTT", "TCCCGTG"];
+        let result = contigs::run_contigs(reads, 3, false, &simplify(Some(2), None, None));
+        assert_eq!(result.unwrap(), ">unitig_1 len=7 cov=2.00\nAAACGTT\n");
+
+        // A short dead end next to a longer branch is clipped
+        let reads = vec!["AAACGTTCAG", "ACGTG"];
+        let result = contigs::run_contigs(reads, 3, false, &simplify(None, Some(5), None));
+        assert_eq!(result.unwrap(), ">unitig_1 len=10 cov=1.14\nAAACGTTCAG\n");
+
+        // Of two equally short branches only the second is clipped
+        let reads = vec!["AAACGTT", "AAACGTG"];
+        let result = contigs::run_contigs(reads, 3, false, &simplify(None, Some(5), None));
+        assert_eq!(result.unwrap(), ">unitig_1 len=7 cov=1.75\nAAACGTG\n");
+
+        // The branch seen once is popped
+        let reads = vec!["AAACGTTCAG", "AAACGATCAG", "AAACGTTCAG"];
+        let result = contigs::run_contigs(reads, 3, false, &simplify(None, None, Some(10)));
+        assert_eq!(result.unwrap(), ">unitig_1 len=10 cov=2.43\nAAACGTTCAG\n");
+    }
+
+    #[test]
+    fn test_debruijn_counts() {
+        let simplify = cli::SimplifyArgs {
+            min_coverage: None,
+            tip_length: None,
+            bubble_length: None,
+        };
+        let debruijn = |format| {
+            debruijn::run_debruijn_graph(
+                vec!["ACGTACGT", "GTAC"],
+                Some(2),
+                false,
+                format,
+                false,
+                true,
+                &simplify,
+            )
+            .unwrap()
+        };
+
+        let adjacency = debruijn(GraphFormat::Text);
+        assert_eq!(
+            adjacency,
+            "(AC, CG) x2\n(CG, GT) x2\n(GT, TA) x2\n(TA, AC) x2\n"
+        );
+        assert!(debruijn(GraphFormat::Dot).contains("\"GT\" -> \"TA\" [label=\"GTA x2\"];"));
+
+        // The counts are read back as parallel edges
+        let result = eulerian::run_eulerian(&adjacency, None, false, false, false).unwrap();
+        assert_eq!(result, "ACGTACGTAC");
+        let result = eulerian::run_eulerian("(AC, CG) x", None, false, false, false);
+        assert_eq!(result.unwrap_err(), "Invalid edge count: (AC, CG) x");
+    }
+
+    #[test]
+    fn test_colored() {
+        use cli::ColoredQuery;
+
+        let samples = |strains: &[(&str, &str)]| {
+            strains
+                .iter()
+                .map(|(name, seq)| colored::Sample {
+                    name: name.to_string(),
+                    sequences: vec![seq.to_string()],
+                })
+                .collect()
+        };
+        // strainB differs from the others by a T -> A substitution
+        let strains = [
+            ("strainA", "AAACGTTCAG"),
+            ("strainB", "AAACGATCAG"),
+            ("strainC", "AAACGTTCAG"),
+        ];
+        let colored = |query| colored::run_colored_graph(samples(&strains), 3, false, query);
+
+        assert_eq!(
+            colored(ColoredQuery::Shared).unwrap(),
+            "AAA\nAAC\nACG\nCAG\nTCA\n"
+        );
+        assert_eq!(
+            colored(ColoredQuery::Unique).unwrap(),
+            "ATC\tstrainB\nCGA\tstrainB\nGAT\tstrainB\n"
+        );
+        assert_eq!(
+            colored(ColoredQuery::Bubbles).unwrap(),
+            "bubble_1\tACG\tTCA\tACGATCA\tstrainB\n\
+             bubble_1\tACG\tTCA\tACGTTCA\tstrainA,strainC\n"
+        );
+
+        // A bubble whose branches were seen in the same samples is not reported
+        let strain = vec![colored::Sample {
+            name: String::from("strainAB"),
+            sequences: vec![String::from("AAACGTTCAG"), String::from("AAACGATCAG")],
+        }];
+        let result = colored::run_colored_graph(strain, 3, false, ColoredQuery::Bubbles);
+        assert_eq!(result.unwrap(), "");
+    }
+
+    #[test]
+    fn test_bidirected() {
+        // ACGT is its own reverse complement, so it is counted once per strand
+        let edges = debruijn::count_edges(vec!["GACGTA"], Some(3), true).unwrap();
+        let graph = bidirected::BidirectedGraph::from_edge_counts(edges).unwrap();
+        let counts: Vec<usize> = graph.edges.iter().map(|edge| edge.count).collect();
+        assert_eq!(counts, vec![1, 1, 1]);
+
+        let mut edges = std::collections::HashMap::new();
+        edges.insert((String::from("AAC"), String::from("ACG")), 2);
+        edges.insert((String::from("CGT"), String::from("GTT")), 1);
+        let result = bidirected::BidirectedGraph::from_edge_counts(edges);
+        assert_eq!(
+            result.err(),
+            Some(String::from(
+                "Edge AACG was not seen as often on both strands"
+            ))
+        );
+
+        let eulerian = |read| eulerian::run_eulerian(read, Some(3), true, false, false);
+        assert_eq!(eulerian("ACGTTGCA").unwrap(), "ACGTTGCA");
+        assert_eq!(eulerian("GATTACGTAC").unwrap(), "GATTACGTAC");
+        assert_eq!(eulerian("ACGTACGTTT").unwrap(), "AAACGTACGT");
+
+        let no_simplify = cli::SimplifyArgs {
+            min_coverage: None,
+            tip_length: None,
+            bubble_length: None,
+        };
+        let result = contigs::run_contigs(vec!["AATCCGA"], 3, true, &no_simplify);
+        assert_eq!(result.unwrap(), ">unitig_1 len=7 cov=1.00\nAATCCGA\n");
+        let result = contigs::run_contigs(vec!["GATTACGTAC"], 3, true, &no_simplify);
+        assert_eq!(
+            result.unwrap(),
+            ">unitig_1 len=6 cov=1.00\nGATTAC\n\
+             >unitig_2 len=4 cov=1.00\nGTAC\n\
+             >unitig_3 len=5 cov=1.50\nTACGT\n"
+        );
+    }
+
+    #[test]
+    fn test_nucleotide() {
+        assert_eq!(nucleotide::reverse("ACGTN"), "NTGCA");
+        assert_eq!(nucleotide::complement("ACGT", false).unwrap(), "TGCA");
+        assert_eq!(
+            nucleotide::reverse_complement("AACGTT", false).unwrap(),
+            "AACGTT"
+        );
+
+        // Ambiguity codes and gaps are complemented, and case is kept
+        assert_eq!(
+            nucleotide::complement("RYKMBVDHSWN-", false).unwrap(),
+            "YRMKVBHDSWN-"
+        );
+        assert_eq!(
+            nucleotide::reverse_complement("acGTn", false).unwrap(),
+            "nACgt"
+        );
+        assert_eq!(
+            nucleotide::complement("ACXT", false),
+            Err(String::from("Invalid character in sequence: X"))
+        );
+
+        // The alphabet is chosen once for all the sequences of an input
+        assert!(nucleotide::is_rna(["GCAUG", "GCAAGC"]));
+        assert!(!nucleotide::is_rna(["GCAUG", "GCTAGC"]));
+        assert!(!nucleotide::is_rna(["GCAAGC"]));
+        assert_eq!(nucleotide::complement("GCAUG", true).unwrap(), "CGUAC");
+        assert_eq!(
+            nucleotide::reverse_complement("GCAAGC", true).unwrap(),
+            "GCUUGC"
+        );
+        assert_eq!(
+            nucleotide::run_revcomp(
+                String::from("GCAUG\nGCAAGC\n"),
+                cli::RevcompMode::ReverseComplement
+            )
+            .unwrap(),
+            "CAUGC\nGCUUGC\n"
+        );
+
+        let no_simplify = cli::SimplifyArgs {
+            min_coverage: None,
+            tip_length: None,
+            bubble_length: None,
+        };
+        let result = contigs::run_contigs(vec!["GCAUGCAAGC"], 3, true, &no_simplify).unwrap();
+        assert!(!result.contains('T'));
+    }
+
+    #[test]
+    fn test_read_fastq() {
+        let records = read_fastq("\n@r1\n\n+\n\n@r2\nACGT\n+r2\nIIII\n\n").unwrap();
+        let records: Vec<(&str, &str, &str)> = records
+            .iter()
+            .map(|fastq| {
+                (
+                    fastq.header.as_str(),
+                    fastq.seq.as_str(),
+                    fastq.qual.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(records, vec![("r1", "", ""), ("r2", "ACGT", "IIII")]);
+
+        assert_eq!(
+            read_fastq("@r1\nACGT\n+\nIIII\n@r2\nAC\n").err(),
+            Some(String::from("Incomplete fastq record: r2"))
+        );
+        assert_eq!(
+            read_fastq("@r1\nACGT\n+\nIII\n").err(),
+            Some(String::from(
+                "Sequence and quality lengths differ in record r1 (4 != 3)"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_paired_debruijn() {
+        let pairs = "4 2\nGAGA|TTGA\nTCGT|GATG\nCGTG|ATGT\nTGGT|TGAG\nGTGA|TGTT\n\
+                     GTGG|GTGA\nTGAG|GTTG\nGGTC|GAGA\nGTCG|AGAT\n";
+        let result = paired::run_paired_debruijn(pairs, None, false, false);
+        assert_eq!(result.unwrap(), "GTGGTCGTGAGATGTTGA");
+
+        // The first Eulerian path of these pairs spells first and second
+        // reads that disagree, so a consistent path has to be searched for
+        let pairs = "AA|CA\nAC|AC\nAC|CC\nCA|CC\nCC|AC\nCC|CA\nCC|CC\nCC|CC\nCC|CC\n";
+        let result = paired::run_paired_debruijn(pairs, Some(0), false, false);
+        assert_eq!(result.unwrap(), "AACACCCCCCAC");
+
+        let result = paired::run_paired_debruijn("AC|GT\nCA|TG\n", Some(0), false, false);
+        assert_eq!(
+            result.unwrap_err(),
+            "No Eulerian path spells a consistent string"
+        );
+    }
+
+    #[test]
+    fn test_debruijn_query() {
+        use cli::GraphQuery;
+
+        let reads = vec!["ACGTACGTTT", "CGTACG", "CCCA"];
+        let query = |query, kmer| {
+            debruijn::run_debruijn_query(reads.clone(), Some(3), false, query, kmer, 100)
+        };
+
+        assert_eq!(
+            query(GraphQuery::Successors, Some("CGT")).unwrap(),
+            "GTA\nGTT\n"
+        );
+        assert_eq!(
+            query(GraphQuery::Predecessors, Some("ACG")).unwrap(),
+            "TAC\n"
+        );
+        assert_eq!(
+            query(GraphQuery::Degree, Some("CGT")).unwrap(),
+            "CGT\t1\t2\n"
+        );
+        assert_eq!(
+            query(GraphQuery::Degree, None).unwrap(),
+            "ACG\t1\t1\nCCA\t1\t0\nCCC\t0\t1\nCGT\t1\t2\n\
+             GTA\t1\t1\nGTT\t1\t1\nTAC\t1\t1\nTTT\t1\t0\n"
+        );
+        assert_eq!(
+            query(GraphQuery::Search, Some("GT")).unwrap(),
+            "CGT\t1\t2\nGTA\t1\t1\nGTT\t1\t1\n"
+        );
+        assert_eq!(
+            query(GraphQuery::Components, None).unwrap(),
+            "component_1\t6\tACG,CGT,GTA,GTT,TAC,TTT\ncomponent_2\t2\tCCA,CCC\n"
+        );
+        assert_eq!(
+            query(GraphQuery::Cycles, None).unwrap(),
+            "ACG->CGT->GTA->TAC->ACG\n"
+        );
+
+        assert_eq!(
+            query(GraphQuery::Successors, None),
+            Err(String::from("This query needs a k-mer, given with --kmer"))
+        );
+        assert_eq!(
+            query(GraphQuery::Predecessors, Some("AAA")),
+            Err(String::from("K-mer AAA is not in the graph"))
+        );
+
+        // Two cycles through ACG, of which only the first is returned
+        let edges = debruijn::count_edges(vec!["ACGTACGAACG"], Some(3), false).unwrap();
+        let graph = debruijn::DeBruijnGraph::from_edge_counts(edges);
+        assert_eq!(graph.cycles(100).0.len(), 2);
+        let (cycles, truncated) = graph.cycles(1);
+        assert_eq!((cycles.len(), truncated), (1, false));
+    }
+
+    #[test]
+    fn test_universal() {
+        for (k, alphabet) in [(3, "ACGT"), (4, "01"), (2, "xyz")] {
+            let size = alphabet.len().pow(k as u32);
+            let mut all_kmers = Vec::new();
+            let symbols: Vec<char> = alphabet.chars().collect();
+            kmer::populate_kmers(0, String::new(), &mut all_kmers, k, &symbols);
+
+            let circular = universal::run_universal(k, alphabet, false).unwrap();
+            assert_eq!(circular.len(), size);
+            assert_eq!(cyclic_composition(&circular, k), all_kmers);
+
+            let linear = universal::run_universal(k, alphabet, true).unwrap();
+            assert_eq!(linear.len(), size + k - 1);
+            let mut kmers: Vec<&str> = (0..size).map(|i| &linear[i..i + k]).collect();
+            kmers.sort();
+            assert_eq!(kmers, all_kmers);
+        }
+
+        assert_eq!(
+            universal::run_universal(12, "ACGT", false),
+            Err(String::from("Too many k-mers: 4^12 is more than 4194304"))
+        );
+    }
+
+    #[test]
+    fn test_perfect_assembly_duplicated_reads() {
+        // Without repeated 3-mers, reads given twice spell the genome twice
+        let genome = "ACAGATTGCCTA";
+        let composition = cyclic_composition(genome, 4);
+        let reads: Vec<&str> = composition
+            .iter()
+            .chain(&composition)
+            .map(|read| read.as_str())
+            .collect();
+
+        let result = perfect_assembly::run_perfect_assembly(reads, false).unwrap();
+        assert_eq!(result.len(), genome.len());
+        assert_eq!(cyclic_composition(&result, 4), composition);
+
+        // A periodic genome can not be told apart from its period
+        let composition = cyclic_composition("ACGACG", 3);
+        let reads: Vec<&str> = composition.iter().map(|read| read.as_str()).collect();
+        let result = perfect_assembly::run_perfect_assembly(reads, false).unwrap();
+        assert_eq!(result, "ACG");
+
+        // When only some reads are given twice, each read is used once
+        let composition = cyclic_composition(genome, 4);
+        let reads: Vec<&str> = composition
+            .iter()
+            .chain(&composition[2..5])
+            .map(|read| read.as_str())
+            .collect();
+        let result = perfect_assembly::run_perfect_assembly(reads, false).unwrap();
+        assert_eq!(result.len(), genome.len());
+        assert_eq!(cyclic_composition(&result, 4), composition);
+
+        let result = perfect_assembly::run_perfect_assembly(vec!["ACAG", "CAGA", "CAGA"], false);
+        assert_eq!(
+            result.unwrap_err(),
+            "The reads do not form a circular genome, with or without the duplicates of read CAGA"
+        );
+    }
+
+    #[test]
+    fn test_perfect_assembly_rc() {
+        // Every other read comes from the reverse strand
+        let genome = "ACAGATTGCCTA";
+        let reads: Vec<String> = cyclic_composition(genome, 5)
+            .iter()
+            .enumerate()
+            .map(|(i, read)| match i % 2 {
+                0 => read.clone(),
+                _ => nucleotide::reverse_complement(read, false).unwrap(),
+            })
+            .collect();
+        let reads: Vec<&str> = reads.iter().map(|read| read.as_str()).collect();
+
+        let result = perfect_assembly::run_perfect_assembly(reads, true).unwrap();
+        let reverse = nucleotide::reverse_complement(genome, false).unwrap();
+        assert_eq!(result.len(), genome.len());
+        assert!([genome, reverse.as_str()]
+            .iter()
+            .any(|strand| cyclic_composition(strand, 5) == cyclic_composition(&result, 5)));
+
+        // GAATTC is its own reverse complement, so both strands are one cycle
+        let composition = cyclic_composition("GAATTC", 4);
+        let reads: Vec<&str> = composition.iter().map(|read| read.as_str()).collect();
+        assert_eq!(
+            perfect_assembly::run_perfect_assembly(reads, true),
+            Err(String::from(
+                "No k splits the reads into two cycles, one per strand"
+            ))
+        );
+    }
 }
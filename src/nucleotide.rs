@@ -0,0 +1,150 @@
+use crate::cli::RevcompMode;
+use crate::{read_fastq, read_mulitfasta};
+
+fn complement_base(base: char, rna: bool) -> Result<char, String> {
+    // Return the complement of a nucleotide, keeping its case. Ambiguity
+    // codes are complemented to the code of the complementary bases
+    //
+    // # Arguments
+    // * `base` - A nucleotide or IUPAC ambiguity code
+    // * `rna` - Whether to complement A to U instead of T
+    //
+    // # Returns
+    // The complementary base or an error if the character is not a nucleotide
+    let complement = match base.to_ascii_uppercase() {
+        'A' if rna => 'U',
+        'A' => 'T',
+        'T' | 'U' => 'A',
+        'C' => 'G',
+        'G' => 'C',
+        'R' => 'Y',
+        'Y' => 'R',
+        'K' => 'M',
+        'M' => 'K',
+        'B' => 'V',
+        'V' => 'B',
+        'D' => 'H',
+        'H' => 'D',
+        'S' => 'S',
+        'W' => 'W',
+        'N' => 'N',
+        '-' => '-',
+        _ => return Err(format!("Invalid character in sequence: {}", base)),
+    };
+    if base.is_ascii_lowercase() {
+        Ok(complement.to_ascii_lowercase())
+    } else {
+        Ok(complement)
+    }
+}
+
+pub fn is_rna<'a>(sequences: impl IntoIterator<Item = &'a str>) -> bool {
+    // An input is taken as RNA if its sequences have uracil and no thymine.
+    // This is decided once for all the sequences of an input, so that they
+    // are all complemented with the same alphabet, even those without A or T
+    //
+    // # Arguments
+    // * `sequences` - The sequences of the input
+    //
+    // # Returns
+    // Whether the sequences are RNA
+    let (mut uracil, mut thymine) = (false, false);
+    for seq in sequences {
+        uracil |= seq.contains(['U', 'u']);
+        thymine |= seq.contains(['T', 't']);
+    }
+    uracil && !thymine
+}
+
+pub fn reverse(seq: &str) -> String {
+    seq.chars().rev().collect()
+}
+
+pub fn complement(seq: &str, rna: bool) -> Result<String, String> {
+    // Given a DNA or RNA sequence, return its complement
+    //
+    // # Arguments
+    // * `seq` - A string containing a sequence
+    // * `rna` - Whether to complement A to U instead of T, as from `is_rna`
+    //
+    // # Returns
+    // A string containing the complement of the input sequence
+    seq.chars().map(|base| complement_base(base, rna)).collect()
+}
+
+pub fn reverse_complement(seq: &str, rna: bool) -> Result<String, String> {
+    // Given a DNA or RNA sequence, return the reverse complement
+    //
+    // # Arguments
+    // * `seq` - A string containing a sequence
+    // * `rna` - Whether to complement A to U instead of T, as from `is_rna`
+    //
+    // # Returns
+    // A string containing the reverse complement of the input sequence
+    seq.chars()
+        .rev()
+        .map(|base| complement_base(base, rna))
+        .collect()
+}
+
+fn transform(seq: &str, mode: RevcompMode, rna: bool) -> Result<String, String> {
+    match mode {
+        RevcompMode::Reverse => Ok(reverse(seq)),
+        RevcompMode::Complement => complement(seq, rna),
+        RevcompMode::ReverseComplement => reverse_complement(seq, rna),
+    }
+}
+
+pub fn run_revcomp(input: String, mode: RevcompMode) -> Result<String, String> {
+    // Reverse, complement or reverse complement every sequence of a fasta or
+    // fastq input, or of each line of a plain input. Fastq qualities are
+    // reversed along with the bases
+    //
+    // # Arguments
+    // * `input` - A string containing the sequences
+    // * `mode` - The transformation to apply
+    //
+    // # Returns
+    // Result containing the transformed sequences, in the input format, or
+    // an error message
+    let mut output = String::new();
+
+    match input.trim_start().chars().next() {
+        Some('>') => {
+            let fastas = read_mulitfasta(input);
+            let rna = is_rna(fastas.iter().map(|fasta| fasta.seq.trim()));
+            for fasta in fastas {
+                let seq = transform(fasta.seq.trim(), mode, rna)?;
+                output.push_str(&format!(">{}\n{}\n", fasta.header, seq));
+            }
+        }
+        Some('@') => {
+            let fastqs = read_fastq(&input)?;
+            let rna = is_rna(fastqs.iter().map(|fastq| fastq.seq.as_str()));
+            for fastq in fastqs {
+                let seq = transform(&fastq.seq, mode, rna)?;
+                let qual = match mode {
+                    RevcompMode::Complement => fastq.qual,
+                    _ => reverse(&fastq.qual),
+                };
+                output.push_str(&format!("@{}\n{}\n+\n{}\n", fastq.header, seq, qual));
+            }
+        }
+        _ => {
+            let lines: Vec<&str> = input
+                .lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty())
+                .collect();
+            let rna = is_rna(lines.iter().copied());
+            for line in lines {
+                output.push_str(&format!("{}\n", transform(line, mode, rna)?));
+            }
+        }
+    }
+
+    if output.is_empty() {
+        return Err("No input sequences".to_string());
+    }
+    Ok(output)
+}
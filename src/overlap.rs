@@ -1,5 +1,121 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use crate::graph_format::{check_gfa_name, dot_quote, GraphFormat};
+use crate::nucleotide::{is_rna, reverse_complement};
 use crate::Fasta;
 
+pub struct OverlapOptions {
+    // Options controlling how overlaps between sequences are detected
+    //
+    // When neither `max_errors` nor `max_error_rate` is set, `k` is the exact
+    // length of the overlap. Otherwise `k` is the minimum overlap length and the
+    // longest overlap within the error bound is reported. With `rc`, reverse
+    // complement orientations are also compared.
+    pub k: usize,
+    pub max_errors: Option<usize>,
+    pub max_error_rate: Option<f64>,
+    pub rc: bool,
+}
+
+impl OverlapOptions {
+    pub fn is_approximate(&self) -> bool {
+        self.max_errors.is_some() || self.max_error_rate.is_some()
+    }
+
+    fn allowed_errors(&self, len: usize) -> usize {
+        // Number of errors allowed in an overlap of length `len`
+        //
+        // # Arguments
+        // * `len` - The length of the overlap
+        //
+        // # Returns
+        // The smallest of the absolute and relative error bounds that are set
+        let by_count = self.max_errors.unwrap_or(usize::MAX);
+        let by_rate = match self.max_error_rate {
+            Some(rate) => (rate * len as f64).floor() as usize,
+            None => usize::MAX,
+        };
+        by_count.min(by_rate)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Overlap {
+    // Struct to hold an overlap between the suffix of `source` and the
+    // prefix of `target`, both given as indices of the input records
+    pub source: usize,
+    pub target: usize,
+    // Strand of each sequence, `+` for forward and `-` for reverse complement
+    pub source_strand: char,
+    pub target_strand: char,
+    // Length of the overlapping suffix of the source
+    pub source_span: usize,
+    // Length of the overlapping prefix of the target
+    pub target_span: usize,
+    // Number of matching bases and number of columns in the alignment
+    pub matches: usize,
+    pub block_len: usize,
+    // Alignment of the target prefix against the source suffix
+    pub cigar: String,
+}
+
+impl Overlap {
+    pub fn orientation(&self) -> String {
+        format!("{}/{}", self.source_strand, self.target_strand)
+    }
+
+    pub fn identity(&self) -> f64 {
+        self.matches as f64 / self.block_len as f64
+    }
+
+    pub fn mirror(&self) -> Overlap {
+        // The same overlap read on the other strand: the reverse complement
+        // of the target overlaps the reverse complement of the source. Source
+        // and target swap roles, so the CIGAR is reversed and I and D swapped
+        Overlap {
+            source: self.target,
+            target: self.source,
+            source_strand: flip_strand(self.target_strand),
+            target_strand: flip_strand(self.source_strand),
+            source_span: self.target_span,
+            target_span: self.source_span,
+            matches: self.matches,
+            block_len: self.block_len,
+            cigar: reverse_cigar(&swap_indels(&self.cigar)),
+        }
+    }
+}
+
+fn flip_strand(strand: char) -> char {
+    if strand == '+' {
+        '-'
+    } else {
+        '+'
+    }
+}
+
+fn swap_indels(cigar: &str) -> String {
+    // Swap the insertions and deletions of a CIGAR string, to take the
+    // other sequence as the reference
+    cigar
+        .chars()
+        .map(|c| match c {
+            'I' => 'D',
+            'D' => 'I',
+            _ => c,
+        })
+        .collect()
+}
+
+struct Alignment {
+    source_span: usize,
+    target_span: usize,
+    matches: usize,
+    block_len: usize,
+    cigar: String,
+}
+
 fn check_ovelrap(seq_s: &str, seq_p: &str, k: usize) -> bool {
     // Check if two sequences overlap
     //
@@ -16,39 +132,572 @@ fn check_ovelrap(seq_s: &str, seq_p: &str, k: usize) -> bool {
     seq_s_suffix == seq_p_prefix
 }
 
-fn mk_overlap_graph(fastas: Vec<Fasta>, k: usize) -> Vec<(String, String)> {
-    // Create an overlap graph from a vector of Fasta structs
+fn compress_cigar(ops: &[u8]) -> String {
+    // Run-length encode a list of alignment operations into a CIGAR string
     //
     // # Arguments
-    // * `fastas` - A vector of Fasta structs
-    // * `k` - The length of the kmer
+    // * `ops` - The alignment operations (`M`, `I` or `D`) in order
+    //
+    // # Returns
+    // The CIGAR string
+    let mut cigar = String::new();
+    let mut i = 0;
+
+    while i < ops.len() {
+        let mut j = i;
+        while j < ops.len() && ops[j] == ops[i] {
+            j += 1;
+        }
+        cigar.push_str(&format!("{}{}", j - i, ops[i] as char));
+        i = j;
+    }
+    cigar
+}
+
+#[derive(Clone, Copy)]
+struct OverlapEnd {
+    // Best alignment of a source suffix against a target prefix that ends at
+    // a given cell of the semi-global alignment matrix
+    errors: usize,
+    // Start of the aligned suffix in the source
+    start: usize,
+    // Whether the alignment opens or closes with an indel
+    opens_with_indel: bool,
+    closes_with_indel: bool,
+}
+
+fn overlap_ends(source: &[u8], target: &[u8], max_target: usize) -> Vec<OverlapEnd> {
+    // Semi-global alignment of every suffix of the source against the
+    // prefixes of the target, keeping only two rows of the matrix. The start
+    // in the source is free and the whole source end is aligned
+    //
+    // # Arguments
+    // * `source` - The source sequence
+    // * `target` - The target sequence
+    // * `max_target` - The longest target prefix to consider
+    //
+    // # Returns
+    // For each target prefix length, the cheapest alignment ending with the
+    // last base of the source. Ties prefer a match, then a deletion
+    let n = target.len().min(max_target);
+    let first_row = |i: usize| {
+        (0..=n)
+            .map(|j| OverlapEnd {
+                errors: j,
+                start: i,
+                opens_with_indel: j > 0,
+                closes_with_indel: j > 0,
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut prev = first_row(0);
+
+    for i in 1..=source.len() {
+        let mut row = first_row(i);
+        for j in 1..=n {
+            let diagonal = prev[j - 1].errors + usize::from(source[i - 1] != target[j - 1]);
+            let deletion = prev[j].errors + 1;
+            let insertion = row[j - 1].errors + 1;
+
+            row[j] = if diagonal <= deletion && diagonal <= insertion {
+                OverlapEnd {
+                    errors: diagonal,
+                    closes_with_indel: false,
+                    ..prev[j - 1]
+                }
+            } else if deletion <= insertion {
+                OverlapEnd {
+                    errors: deletion,
+                    closes_with_indel: true,
+                    ..prev[j]
+                }
+            } else {
+                OverlapEnd {
+                    errors: insertion,
+                    opens_with_indel: j == 1 || row[j - 1].opens_with_indel,
+                    closes_with_indel: true,
+                    ..row[j - 1]
+                }
+            };
+        }
+        prev = row;
+    }
+    prev
+}
+
+fn align_banded(source: &[u8], target: &[u8], band: usize) -> Alignment {
+    // Global alignment of two sequences that only stores the cells within
+    // `band` of the main diagonal
+    //
+    // # Arguments
+    // * `source` - The source suffix
+    // * `target` - The target prefix
+    // * `band` - The maximum distance from the main diagonal, at least the
+    //   difference in length of the two sequences
+    //
+    // # Returns
+    // The alignment, with matches preferred over indels in the traceback
+    let (m, n) = (source.len(), target.len());
+    let width = 2 * band + 1;
+    let inf = usize::MAX / 2;
+    // Cell (i, j) is stored at column j + band - i of row i
+    let mut dp = vec![vec![inf; width]; m + 1];
+    let cell = |dp: &Vec<Vec<usize>>, i: usize, j: usize| {
+        if j + band < i || j > i + band {
+            inf
+        } else {
+            dp[i][j + band - i]
+        }
+    };
+
+    for i in 0..=m {
+        for j in i.saturating_sub(band)..=(i + band).min(n) {
+            let errors = if i == 0 {
+                j
+            } else if j == 0 {
+                i
+            } else {
+                let diagonal =
+                    cell(&dp, i - 1, j - 1) + usize::from(source[i - 1] != target[j - 1]);
+                diagonal
+                    .min(cell(&dp, i - 1, j) + 1)
+                    .min(cell(&dp, i, j - 1) + 1)
+            };
+            dp[i][j + band - i] = errors;
+        }
+    }
+
+    let mut ops = Vec::new();
+    let mut matches = 0;
+    let (mut i, mut j) = (m, n);
+
+    while i > 0 || j > 0 {
+        let current = cell(&dp, i, j);
+        if i > 0 && j > 0 {
+            let is_match = source[i - 1] == target[j - 1];
+            if current == cell(&dp, i - 1, j - 1) + usize::from(!is_match) {
+                ops.push(b'M');
+                matches += usize::from(is_match);
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+        }
+        if i > 0 && current == cell(&dp, i - 1, j) + 1 {
+            ops.push(b'D');
+            i -= 1;
+        } else {
+            ops.push(b'I');
+            j -= 1;
+        }
+    }
+    ops.reverse();
+
+    Alignment {
+        source_span: m,
+        target_span: n,
+        matches,
+        block_len: ops.len(),
+        cigar: compress_cigar(&ops),
+    }
+}
+
+fn find_approximate_overlap(
+    seq_s: &str,
+    seq_p: &str,
+    options: &OverlapOptions,
+) -> Option<Alignment> {
+    // Find the longest overlap between the suffix of `seq_s` and the prefix of
+    // `seq_p` that is at least `k` long and within the error bound. Overlaps
+    // that open or close with an indel are skipped, and ties in length go to
+    // the overlap with the fewest errors
+    //
+    // # Arguments
+    // * `seq_s` - The subject sequence
+    // * `seq_p` - The query sequence
+    // * `options` - The overlap options
+    //
+    // # Returns
+    // The alignment of the overlap, or None if the sequences don't overlap
+    let seq_s = seq_s.as_bytes();
+    let seq_p = seq_p.as_bytes();
+    let max_errors = options.allowed_errors(seq_s.len());
+    let ends = overlap_ends(seq_s, seq_p, seq_s.len().saturating_add(max_errors));
+
+    // Candidates as (source span, errors, target span)
+    let mut candidates: Vec<(usize, usize, usize)> = ends
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, end)| !end.opens_with_indel && !end.closes_with_indel)
+        .map(|(j, end)| (seq_s.len() - end.start, end.errors, j))
+        .filter(|&(len, errors, _)| {
+            len >= options.k.max(1) && errors <= options.allowed_errors(len)
+        })
+        .collect();
+    candidates.sort_by_key(|&(len, errors, j)| (Reverse(len), errors, j));
+
+    // The banded traceback may pick another path of the same cost, so check
+    // the ends of the alignment again
+    candidates.into_iter().find_map(|(len, errors, j)| {
+        let alignment = align_banded(&seq_s[seq_s.len() - len..], &seq_p[..j], errors);
+        let clean = alignment.cigar.ends_with('M')
+            && alignment
+                .cigar
+                .trim_start_matches(char::is_numeric)
+                .starts_with('M');
+        clean.then_some(alignment)
+    })
+}
+
+fn find_longest_exact_overlap(seq_s: &str, seq_p: &str, k: usize) -> Option<usize> {
+    // Find the length of the longest suffix of `seq_s` that is a prefix of
+    // `seq_p` with the Knuth-Morris-Pratt failure function of `seq_p`
+    //
+    // # Arguments
+    // * `seq_s` - The subject sequence
+    // * `seq_p` - The query sequence
+    // * `k` - The minimum length of the overlap
+    //
+    // # Returns
+    // The length of the overlap, or None if it is shorter than `k`
+    let pattern = seq_p.as_bytes();
+    if pattern.is_empty() {
+        return None;
+    }
+    let mut failure = vec![0; pattern.len()];
+    let mut q = 0;
+
+    for i in 1..pattern.len() {
+        while q > 0 && pattern[i] != pattern[q] {
+            q = failure[q - 1];
+        }
+        if pattern[i] == pattern[q] {
+            q += 1;
+        }
+        failure[i] = q;
+    }
+
+    q = 0;
+    for &base in seq_s.as_bytes() {
+        if q == pattern.len() {
+            q = failure[q - 1];
+        }
+        while q > 0 && base != pattern[q] {
+            q = failure[q - 1];
+        }
+        if base == pattern[q] {
+            q += 1;
+        }
+    }
+    (q >= k.max(1)).then_some(q)
+}
+
+fn find_overlap(seq_s: &str, seq_p: &str, options: &OverlapOptions) -> Option<Alignment> {
+    // Check if the suffix of one sequence overlaps the prefix of another one
+    //
+    // # Arguments
+    // * `seq_s` - The subject sequence
+    // * `seq_p` - The query sequence
+    // * `options` - The overlap options
+    //
+    // # Returns
+    // The alignment of the overlap, if any
+    let len = if !options.is_approximate() {
+        check_ovelrap(seq_s, seq_p, options.k).then_some(options.k)?
+    } else if options.allowed_errors(seq_s.len()) == 0 {
+        // No overlap may have errors, so the longest one is found exactly
+        find_longest_exact_overlap(seq_s, seq_p, options.k)?
+    } else {
+        return find_approximate_overlap(seq_s, seq_p, options);
+    };
+    Some(Alignment {
+        source_span: len,
+        target_span: len,
+        matches: len,
+        block_len: len,
+        cigar: format!("{}M", len),
+    })
+}
+
+pub fn check_records(fastas: &[Fasta], k: usize) -> Result<(), String> {
+    // Check that the records can be used as nodes of the overlap graph
+    //
+    // # Arguments
+    // * `fastas` - A slice of Fasta structs
+    // * `k` - The length of the overlap
+    //
+    // # Returns
+    // An empty Ok() or an error if an ID is duplicated or a sequence is
+    // shorter than `k`
+    let mut headers = HashSet::new();
+
+    for fasta in fastas {
+        if !headers.insert(fasta.header.as_str()) {
+            return Err(format!("Duplicate sequence ID: {}", fasta.header));
+        }
+        if fasta.seq.len() < k {
+            return Err(format!(
+                "Sequence {} is shorter than k ({} < {})",
+                fasta.header,
+                fasta.seq.len(),
+                k
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn mk_overlap_graph(
+    fastas: &[Fasta],
+    options: &OverlapOptions,
+) -> Result<Vec<Overlap>, String> {
+    // Create an overlap graph from a slice of Fasta structs. Nodes are the
+    // indices of the records. When `options.rc` is set, every pair is compared
+    // in all four relative orientations. An overlap and its mirror on the
+    // other strand are the same overlap, so only the one whose source comes
+    // first in the input is reported
+    //
+    // # Arguments
+    // * `fastas` - A slice of Fasta structs
+    // * `options` - The overlap options
     //
     // # Returns
-    // A vector of tuples containing the headers of the sequences that overlap
+    // A vector with the overlaps between the sequences, or an error if a
+    // reverse complement can't be computed
+    let mut oriented = Vec::new();
+    let rna = is_rna(fastas.iter().map(|fasta| fasta.seq.as_str()));
+
+    for fasta in fastas.iter() {
+        let mut strands = vec![('+', fasta.seq.clone())];
+        if options.rc {
+            strands.push(('-', reverse_complement(&fasta.seq, rna)?));
+        }
+        oriented.push(strands);
+    }
+
     let mut graph = Vec::new();
 
-    for i in fastas.iter() {
-        for j in fastas.iter() {
-            if i.header != j.header && check_ovelrap(&i.seq, &j.seq, k) {
-                graph.push((i.header.clone(), j.header.clone()));
+    for (i, strands_i) in oriented.iter().enumerate() {
+        for (j, strands_j) in oriented.iter().enumerate() {
+            if i == j || (options.rc && j < i) {
+                continue;
+            }
+            for (strand_s, seq_s) in strands_i {
+                for (strand_p, seq_p) in strands_j {
+                    if let Some(alignment) = find_overlap(seq_s, seq_p, options) {
+                        graph.push(Overlap {
+                            source: i,
+                            target: j,
+                            source_strand: *strand_s,
+                            target_strand: *strand_p,
+                            source_span: alignment.source_span,
+                            target_span: alignment.target_span,
+                            matches: alignment.matches,
+                            block_len: alignment.block_len,
+                            cigar: alignment.cigar,
+                        });
+                    }
+                }
             }
         }
     }
-    graph
+    Ok(graph)
 }
 
-fn generate_formated_output(graph: Vec<(String, String)>) -> String {
-    // Generate a formated output from a vector of tuples
+pub fn generate_formated_output(
+    graph: Vec<Overlap>,
+    fastas: &[Fasta],
+    options: &OverlapOptions,
+) -> String {
+    // Generate a formated output from a vector of overlaps
     //
     // # Arguments
-    // * `graph` - A vector of tuples containing the headers of the sequences that overlap
+    // * `graph` - A vector with the overlaps between the sequences
+    // * `fastas` - The records the overlap indices refer to
+    // * `options` - The overlap options, used to decide whether to report the
+    //               orientation and the overlap length, identity and CIGAR
     //
     // # Returns
     // A string containing the formated output
     let mut output = String::new();
 
-    for (s, p) in graph {
-        output.push_str(&format!("{} {}\n", s, p));
+    for overlap in graph {
+        output.push_str(&format!(
+            "{} {}",
+            fastas[overlap.source].header, fastas[overlap.target].header
+        ));
+        if options.rc {
+            output.push_str(&format!(" {}", overlap.orientation()));
+        }
+        if options.is_approximate() {
+            output.push_str(&format!(
+                " {} {:.3} {}",
+                overlap.source_span,
+                overlap.identity(),
+                overlap.cigar
+            ));
+        }
+        output.push('\n');
+    }
+
+    output.pop();
+
+    output
+}
+
+pub fn generate_gfa_output(
+    graph: Vec<Overlap>,
+    fastas: &[Fasta],
+    segments: &[usize],
+) -> Result<String, String> {
+    // Generate a GFA 1.0 output from a vector of overlaps
+    //
+    // # Arguments
+    // * `graph` - A vector with the overlaps between the sequences
+    // * `fastas` - The records the overlap indices refer to
+    // * `segments` - The indices of the records to write as segments
+    //
+    // # Returns
+    // A string with a header line, one S line per segment and one L line per
+    // overlap, or an error if a sequence ID is not a valid segment name
+    let mut output = String::from("H\tVN:Z:1.0\n");
+
+    for &i in segments {
+        check_gfa_name(&fastas[i].header)?;
+        output.push_str(&format!("S\t{}\t{}\n", fastas[i].header, fastas[i].seq));
+    }
+    for overlap in graph {
+        output.push_str(&format!(
+            "L\t{}\t{}\t{}\t{}\t{}\n",
+            fastas[overlap.source].header,
+            overlap.source_strand,
+            fastas[overlap.target].header,
+            overlap.target_strand,
+            overlap.cigar
+        ));
+    }
+
+    output.pop();
+
+    Ok(output)
+}
+
+pub fn generate_dot_output(
+    graph: Vec<Overlap>,
+    fastas: &[Fasta],
+    segments: &[usize],
+    collapse_labels: bool,
+) -> String {
+    // Generate a DOT output from a vector of overlaps, with each edge labelled
+    // with the overlap length and, for reverse complement edges, the orientation
+    //
+    // # Arguments
+    // * `graph` - A vector with the overlaps between the sequences
+    // * `fastas` - The records the overlap indices refer to
+    // * `segments` - The indices of the records to write as nodes
+    // * `collapse_labels` - Whether to draw the nodes as points without labels
+    //
+    // # Returns
+    // A string containing the DOT digraph
+    let mut output = String::from("digraph overlap {\n");
+    if collapse_labels {
+        output.push_str("    node [shape=point, label=\"\"];\n");
+    }
+
+    for &i in segments {
+        output.push_str(&format!("    {};\n", dot_quote(&fastas[i].header)));
+    }
+    for overlap in graph {
+        let mut label = overlap.source_span.to_string();
+        if overlap.orientation() != "+/+" {
+            label.push_str(&format!(" {}", overlap.orientation()));
+        }
+        output.push_str(&format!(
+            "    {} -> {} [label=\"{}\"];\n",
+            dot_quote(&fastas[overlap.source].header),
+            dot_quote(&fastas[overlap.target].header),
+            label
+        ));
+    }
+    output.push('}');
+
+    output
+}
+
+fn reverse_cigar(cigar: &str) -> String {
+    // Reverse the order of the operations of a CIGAR string
+    //
+    // # Arguments
+    // * `cigar` - The CIGAR string
+    //
+    // # Returns
+    // The CIGAR string read from the end
+    let mut runs = Vec::new();
+    let mut start = 0;
+
+    for (i, c) in cigar.char_indices() {
+        if !c.is_ascii_digit() {
+            runs.push(&cigar[start..=i]);
+            start = i + 1;
+        }
+    }
+    runs.reverse();
+    runs.concat()
+}
+
+pub fn generate_paf_output(graph: Vec<Overlap>, fastas: &[Fasta]) -> String {
+    // Generate a PAF output from a vector of overlaps, using the source as
+    // the query and the target as the reference. Coordinates are given on the
+    // forward strand of each sequence
+    //
+    // # Arguments
+    // * `graph` - A vector with the overlaps between the sequences
+    // * `fastas` - The records the overlap indices refer to
+    //
+    // # Returns
+    // A string with one PAF record per overlap
+    let mut output = String::new();
+
+    for overlap in graph {
+        let query = &fastas[overlap.source];
+        let target = &fastas[overlap.target];
+
+        let (query_start, query_end) = match overlap.source_strand {
+            '+' => (query.seq.len() - overlap.source_span, query.seq.len()),
+            _ => (0, overlap.source_span),
+        };
+        let (target_start, target_end) = match overlap.target_strand {
+            '+' => (0, overlap.target_span),
+            _ => (target.seq.len() - overlap.target_span, target.seq.len()),
+        };
+        let strand = if overlap.source_strand == overlap.target_strand {
+            '+'
+        } else {
+            '-'
+        };
+
+        // The overlap CIGAR uses the source as the reference, PAF uses the target
+        let mut cigar = swap_indels(&overlap.cigar);
+        if overlap.target_strand == '-' {
+            cigar = reverse_cigar(&cigar);
+        }
+
+        output.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t255\tcg:Z:{}\n",
+            query.header,
+            query.seq.len(),
+            query_start,
+            query_end,
+            strand,
+            target.header,
+            target.seq.len(),
+            target_start,
+            target_end,
+            overlap.matches,
+            overlap.block_len,
+            cigar
+        ));
     }
 
     output.pop();
@@ -56,22 +705,51 @@ fn generate_formated_output(graph: Vec<(String, String)>) -> String {
     output
 }
 
-pub fn run_overlap_graph(input: Vec<Fasta>, k: usize) -> Result<String, String> {
+pub fn run_overlap_graph(
+    input: Vec<Fasta>,
+    options: OverlapOptions,
+    format: GraphFormat,
+    collapse_labels: bool,
+    paf: bool,
+) -> Result<String, String> {
     // Run the overlap graph problem
     //
     // # Arguments
     // * `input` - A vector of Fasta structs
-    // * `k` - The length of the kmer
+    // * `options` - The overlap options
+    // * `format` - The output format
+    // * `collapse_labels` - Whether to draw unlabelled nodes in DOT output
+    // * `paf` - Write the overlaps as PAF records instead of the graph
     //
     // # Returns
     // Result containing a string with the formated output or an error message
     if input.is_empty() {
         return Err(String::from("No input sequences"));
-    } else if input.is_empty() {
+    } else if input.len() == 1 {
         return Err(String::from("Only one input sequence"));
     }
+    if let Some(rate) = options.max_error_rate {
+        if !(0.0..1.0).contains(&rate) {
+            return Err(format!("Invalid error rate: {}", rate));
+        }
+    }
+
+    check_records(&input, options.k)?;
 
-    let graph = mk_overlap_graph(input, k);
+    let graph = mk_overlap_graph(&input, &options)?;
 
-    Ok(generate_formated_output(graph))
+    let segments: Vec<usize> = (0..input.len()).collect();
+    if paf {
+        return Ok(generate_paf_output(graph, &input));
+    }
+    match format {
+        GraphFormat::Text => Ok(generate_formated_output(graph, &input, &options)),
+        GraphFormat::Gfa => generate_gfa_output(graph, &input, &segments),
+        GraphFormat::Dot => Ok(generate_dot_output(
+            graph,
+            &input,
+            &segments,
+            collapse_labels,
+        )),
+    }
 }
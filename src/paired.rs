@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+
+use crate::eulerian::{find_eulerian_walk, find_start_node, Multigraph};
+
+// Largest number of edges the search for a consistent walk may undo before it
+// gives up. The walks branch at every repeat, so the search can otherwise take
+// time exponential in the number of repeats
+const MAX_BACKTRACKS: usize = 1_000_000;
+
+// The two reads of a pair
+type ReadPair<'a> = (&'a str, &'a str);
+
+fn parse_pairs(input: &str) -> Result<(Vec<ReadPair<'_>>, Option<usize>), String> {
+    // Parse the read pairs of the input, one `AAG|GCA` pair per line. The
+    // first line may instead give `k d`, as in the Rosalind datasets
+    //
+    // # Arguments
+    // * `input` - A string with one pair per line
+    //
+    // # Returns
+    // The pairs and the distance given in the input, if any, or an error if
+    // a line is not a pair or the pairs have different lengths
+    let mut lines = input
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .peekable();
+
+    let mut d = None;
+    if let Some(first) = lines.peek() {
+        let numbers: Vec<Result<usize, _>> = first.split_whitespace().map(str::parse).collect();
+        if let [Ok(_), Ok(distance)] = numbers.as_slice() {
+            d = Some(*distance);
+            lines.next();
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for line in lines {
+        match line.split_once('|') {
+            Some((first, second)) if !first.is_empty() && first.len() == second.len() => {
+                pairs.push((first, second))
+            }
+            _ => return Err(format!("Invalid read pair: {}", line)),
+        }
+    }
+
+    match pairs.first() {
+        None => Err("No read pairs provided".to_string()),
+        Some((first, _)) if pairs.iter().any(|(a, _)| a.len() != first.len()) => {
+            Err("All the read pairs must have the same length".to_string())
+        }
+        Some(_) => Ok((pairs, d)),
+    }
+}
+
+fn spell_gapped_walk(walk: &[ReadPair], gap: usize) -> Option<String> {
+    // Spell the string of a walk over gapped pairs. The first and second
+    // parts of the pairs spell two strings `gap` bases apart, which must
+    // agree where they overlap
+    //
+    // # Arguments
+    // * `walk` - The pairs visited, in order
+    // * `gap` - The distance between the starts of the two parts of a pair
+    //
+    // # Returns
+    // The spelled string, or None if the two strings disagree
+    let mut prefix = walk[0].0.to_string();
+    let mut suffix = walk[0].1.to_string();
+    for (first, second) in &walk[1..] {
+        prefix.push_str(&first[first.len() - 1..]);
+        suffix.push_str(&second[second.len() - 1..]);
+    }
+
+    if gap > prefix.len() {
+        return None;
+    }
+    if prefix[gap..] != suffix[..suffix.len() - gap] {
+        return None;
+    }
+    Some(prefix + &suffix[suffix.len() - gap..])
+}
+
+fn split_pair(label: &str) -> ReadPair<'_> {
+    label.split_once('|').unwrap()
+}
+
+fn find_consistent_walk(
+    graph: &Multigraph,
+    start: usize,
+    gap: usize,
+) -> Result<Vec<usize>, String> {
+    // Search the Eulerian walks from `start` for one whose pairs spell a
+    // consistent string. The walk is extended one edge at a time, and a
+    // branch is abandoned as soon as a new base of the first string
+    // disagrees with the second string. The search stops after
+    // `MAX_BACKTRACKS` abandoned edges
+    //
+    // # Arguments
+    // * `graph` - The paired de Bruijn graph
+    // * `start` - The node the walks start at
+    // * `gap` - The distance between the starts of the two parts of a pair
+    //
+    // # Returns
+    // The nodes of a consistent walk, or an error if there is none or the
+    // search gave up
+    let total = graph.edge_count();
+    let mut used: Vec<Vec<bool>> = (0..graph.nodes().len())
+        .map(|v| vec![false; graph.targets(v).len()])
+        .collect();
+
+    let (first, second) = split_pair(&graph.nodes()[start]);
+    let mut prefix = first.as_bytes().to_vec();
+    let mut suffix = second.as_bytes().to_vec();
+    let mut walk = vec![start];
+    // For each step of the walk, the edge taken, and the next edge to try
+    let mut taken: Vec<usize> = Vec::new();
+    let mut next = vec![0];
+    let mut backtracks = 0;
+
+    while walk.len() <= total {
+        let v = *walk.last().unwrap();
+        let targets = graph.targets(v);
+        let depth = walk.len() - 1;
+
+        let mut step = None;
+        while next[depth] < targets.len() {
+            let i = next[depth];
+            next[depth] += 1;
+            // Parallel edges lead to the same walks, so only the first unused one is tried
+            let repeated = (0..i).any(|j| targets[j] == targets[i] && !used[v][j]);
+            if used[v][i] || repeated {
+                continue;
+            }
+            let (first, second) = split_pair(&graph.nodes()[targets[i]]);
+            let base = first.as_bytes()[first.len() - 1];
+            let p = prefix.len();
+            if p >= gap && p - gap < suffix.len() && suffix[p - gap] != base {
+                continue;
+            }
+            prefix.push(base);
+            suffix.push(second.as_bytes()[second.len() - 1]);
+            step = Some(i);
+            break;
+        }
+
+        match step {
+            Some(i) => {
+                used[v][i] = true;
+                taken.push(i);
+                walk.push(targets[i]);
+                next.push(0);
+            }
+            None => {
+                // Backtrack to the previous node and undo the edge that led here
+                let i = taken
+                    .pop()
+                    .ok_or("No Eulerian path spells a consistent string")?;
+                backtracks += 1;
+                if backtracks > MAX_BACKTRACKS {
+                    return Err(format!(
+                        "No consistent Eulerian path found within {} backtracking steps",
+                        MAX_BACKTRACKS
+                    ));
+                }
+                next.pop();
+                walk.pop();
+                prefix.pop();
+                suffix.pop();
+                used[*walk.last().unwrap()][i] = false;
+            }
+        }
+    }
+    Ok(walk)
+}
+
+fn generate_adjacency_output(graph: &Multigraph) -> String {
+    // Write the graph as an `A -> B,C` adjacency list, sorted alphabetically
+    let mut adjacency: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (v, node) in graph.nodes().iter().enumerate() {
+        for &to in graph.targets(v) {
+            adjacency.entry(node).or_default().push(&graph.nodes()[to]);
+        }
+    }
+
+    let mut output = String::new();
+    for (node, mut targets) in adjacency {
+        targets.sort();
+        output.push_str(&format!("{} -> {}\n", node, targets.join(",")));
+    }
+    output
+}
+
+pub fn run_paired_debruijn(
+    input: &str,
+    d: Option<usize>,
+    adjacency: bool,
+    spell: bool,
+) -> Result<String, String> {
+    // Reconstruct a string from its read pairs: (k,d)-mer pairs of k-mers
+    // that start k+d bases apart. Each pair is an edge of the paired de
+    // Bruijn graph, from the pair of its prefixes to the pair of its
+    // suffixes, and the string is spelled by an Eulerian path whose two
+    // strands agree
+    //
+    // # Arguments
+    // * `input` - One `AAG|GCA` pair per line, optionally after a `k d` line
+    // * `d` - The distance between the two k-mers of a pair. Overrides the one in the input
+    // * `adjacency` - Return the adjacency list of the paired graph instead
+    // * `spell` - Spell the pairs in the given order, as a gapped genome path
+    //
+    // # Returns
+    // Result containing the reconstructed string or an error message
+    let (pairs, input_d) = parse_pairs(input)?;
+    let d = d
+        .or(input_d)
+        .ok_or("Missing the distance d between the reads of a pair")?;
+    let k = pairs[0].0.len();
+    if k < 2 {
+        return Err("The reads of a pair must be at least 2 bases long".to_string());
+    }
+
+    if spell {
+        return spell_gapped_walk(&pairs, k + d)
+            .ok_or("The read pairs do not spell a consistent string".to_string());
+    }
+
+    let mut graph = Multigraph::default();
+    for (first, second) in &pairs {
+        graph.add_edge(
+            &format!("{}|{}", &first[..k - 1], &second[..k - 1]),
+            &format!("{}|{}", &first[1..], &second[1..]),
+            1,
+        );
+    }
+    if adjacency {
+        return Ok(generate_adjacency_output(&graph));
+    }
+
+    // Nodes hold (k-1)-mers, so their parts start k+d bases apart
+    let (walk, _) = find_eulerian_walk(&graph)?;
+    let walk: Vec<ReadPair> = walk.into_iter().map(split_pair).collect();
+    if let Some(spelled) = spell_gapped_walk(&walk, k + d) {
+        return Ok(spelled);
+    }
+
+    let (start, _) = find_start_node(&graph)?;
+    let walk = find_consistent_walk(&graph, start, k + d)?;
+    let walk: Vec<ReadPair> = walk
+        .into_iter()
+        .map(|v| split_pair(&graph.nodes()[v]))
+        .collect();
+    spell_gapped_walk(&walk, k + d).ok_or("No Eulerian path spells a consistent string".to_string())
+}
@@ -1,91 +1,141 @@
-use std::collections::HashMap;
 use std::collections::HashSet;
 
-use crate::debruijn::build_nodes;
+use crate::debruijn::{count_edges, DeBruijnGraph};
+use crate::eulerian::{find_eulerian_walk, spell_walk, Multigraph};
 
-fn create_string_from_nodes(nodes: HashSet<(String, String)>) -> Result<String, &'static str> {
-    // Creates a string from a set of nodes trying to follow the eulerian path
+fn find_shortest_circular_string(circular_string: &str) -> String {
+    // Finds the shortest circular string that repeated spells a circular
+    // string, as when every read was given more than once. A genome that is
+    // itself periodic can not be told apart and is collapsed as well
     //
     // # Arguments
-    // * `nodes` - A set of nodes
+    // * `circular_string` - A circular string
     //
     // # Returns
-    // * `Result<String, &'static str>` - A string constructed from the nodes
-
-    let nodes_hm: HashMap<String, String> = nodes.into_iter().collect();
-
-    let mut constructed_string = String::new();
-    let mut next_node = String::new();
-    let mut visited_nodes = HashSet::new();
-
-    for key in nodes_hm.keys() {
-        constructed_string = key.clone();
-        visited_nodes.insert(key.clone());
-        next_node = nodes_hm.get(key).unwrap().clone();
-
-        while !visited_nodes.contains(&next_node) {
-            visited_nodes.insert(next_node.clone());
-            constructed_string += &next_node.chars().last().unwrap().to_string();
-            next_node = nodes_hm.get(&next_node).unwrap().clone();
+    // * `String` - The shortest circular string
+    let len = circular_string.len();
+    for i in 1..len {
+        if len.is_multiple_of(i) && circular_string[i..] == circular_string[..len - i] {
+            return circular_string[..i].to_string();
         }
+    }
+    circular_string.to_string()
+}
+
+fn spell_strand_cycles(graph: &DeBruijnGraph) -> Option<String> {
+    // Spell the genome when the graph is exactly two cycles, one per strand
+    //
+    // # Arguments
+    // * `graph` - The de Bruijn graph of the reads and their reverse complements
+    //
+    // # Returns
+    // The circular string of the cycle through the smallest k-mer, or None if
+    // a k-mer branches or the graph does not have two components
+    let is_cycle = (0..graph.nodes.len())
+        .all(|node| graph.in_degree(node) == 1 && graph.out_degree(node) == 1);
+    if !is_cycle || graph.connected_components().len() != 2 {
+        return None;
+    }
 
-        if visited_nodes.len() == nodes_hm.len() {
-            return Ok(constructed_string);
+    let mut circular_string = String::new();
+    let mut node = 0;
+    loop {
+        circular_string.push_str(&graph.nodes[node][..1]);
+        node = graph.successors(node)[0];
+        if node == 0 {
+            return Some(circular_string);
         }
     }
-    Err("Not all nodes were visited and the string could not be constructed")
 }
 
-fn find_shortest_circular_string(linear_string: &str) -> Result<String, &'static str> {
-    // Finds the shortest circular string from a linear string
+fn run_stranded_assembly(sequences: Vec<&str>) -> Result<String, String> {
+    // Assemble reads that come from either strand. The reads are split into
+    // k-mers, from the read length down, until the de Bruijn graph of the
+    // k-mers and their reverse complements is two cycles, one per strand
     //
     // # Arguments
-    // * `linear_string` - A linear stringify!
+    // * `sequences` - A vector of sequences
     //
     // # Returns
-    // * `Result<String, &'static str>` - The shortest circular string
+    // * `Result<String, String>` - The circular string of one of the strands
+    let read_length = sequences
+        .iter()
+        .map(|seq| seq.len())
+        .min()
+        .ok_or("No sequences provided")?;
 
-    for i in 1..linear_string.len() {
-        if linear_string.starts_with(&linear_string[i..]) {
-            return Ok(linear_string[..i].to_string());
+    for k in (2..=read_length).rev() {
+        let edges = count_edges(sequences.clone(), Some(k - 1), true)?;
+        let graph = DeBruijnGraph::from_edge_counts(edges);
+        if let Some(circular_string) = spell_strand_cycles(&graph) {
+            return Ok(circular_string);
         }
     }
-    Err("No circular string found")
+    Err("No k splits the reads into two cycles, one per strand".to_string())
 }
 
-pub fn run_perfect_assembly(sequences: Vec<&str>) -> Result<String, String> {
-    // Runs the perfect assembly algorithm
+fn spell_read_cycle(sequences: Vec<&str>) -> Result<String, String> {
+    // Spell the Eulerian cycle of the de Bruijn graph with one edge per read
     //
     // # Arguments
     // * `sequences` - A vector of sequences
     //
     // # Returns
     // * `Result<String, String>` - The shortest circular string
-    let nodes = build_nodes(sequences, false);
-    match nodes {
-        Ok(nodes) => {
-            let str_from_nodes = create_string_from_nodes(nodes);
-            match str_from_nodes {
-                Ok(str_from_nodes) => {
-                    let shortest_circular_string = find_shortest_circular_string(&str_from_nodes);
-                    match shortest_circular_string {
-                        Ok(shortest_circular_string) => {
-                            return Ok(shortest_circular_string);
-                        }
-                        Err(e) => {
-                            return Err(e.to_string());
-                        }
-                    }
-                }
-                Err(e) => {
-                    return Err(e.to_string());
-                }
-            }
-        }
-        Err(e) => {
-            return Err(e);
-        }
+    let edges = count_edges(sequences, None, false)?;
+    let graph = Multigraph::from_edge_counts(edges);
+
+    let (walk, is_cycle) = find_eulerian_walk(&graph)?;
+    if !is_cycle {
+        return Err("The reads do not form a circular genome".to_string());
     }
+    // The spelled cycle ends with its first node again
+    let linear_string = spell_walk(&walk)?;
+    let circular_string = &linear_string[..linear_string.len() - walk[0].len()];
+
+    Ok(find_shortest_circular_string(circular_string))
 }
 
+pub fn run_perfect_assembly(sequences: Vec<&str>, rc: bool) -> Result<String, String> {
+    // Runs the perfect assembly algorithm. Each read is an edge of the de
+    // Bruijn graph, kept as many times as it is given, and the genome is
+    // spelled by an Eulerian cycle, so that repeated k-mers do not drop edges.
+    // If only some reads are duplicated, the counts can not form a cycle and
+    // every read is used once instead
+    //
+    // # Arguments
+    // * `sequences` - A vector of sequences
+    // * `rc` - Whether the reads come from both strands of the genome
+    //
+    // # Returns
+    // * `Result<String, String>` - The shortest circular string
+    if rc {
+        return run_stranded_assembly(sequences);
+    }
+    let error = match spell_read_cycle(sequences.clone()) {
+        Ok(circular_string) => return Ok(circular_string),
+        Err(error) => error,
+    };
 
+    let mut seen = HashSet::new();
+    let mut unique = Vec::new();
+    let mut duplicate = None;
+    for seq in sequences {
+        if seen.insert(seq) {
+            unique.push(seq);
+        } else {
+            duplicate.get_or_insert(seq);
+        }
+    }
+    let Some(duplicate) = duplicate else {
+        return Err(error);
+    };
+    let circular_string = spell_read_cycle(unique).map_err(|_| {
+        format!(
+            "The reads do not form a circular genome, with or without the duplicates of read {}",
+            duplicate
+        )
+    })?;
+    eprintln!("Warning: the read counts do not form a circular genome, so each read was used once");
+    Ok(circular_string)
+}
@@ -0,0 +1,171 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::cli::SimplifyArgs;
+use crate::debruijn::DeBruijnGraph;
+use crate::unitig::{find_unitigs, Unitig};
+
+fn remove_low_coverage_edges(graph: &mut DeBruijnGraph, min_coverage: usize) -> usize {
+    // Remove the edges seen fewer than `min_coverage` times
+    //
+    // # Returns
+    // The number of edges removed
+    let removed: Vec<bool> = graph
+        .edges
+        .iter()
+        .map(|edge| edge.count < min_coverage)
+        .collect();
+    graph.remove_edges(&removed);
+
+    removed.iter().filter(|&&r| r).count()
+}
+
+fn tip_branch_point(graph: &DeBruijnGraph, unitig: &Unitig) -> Option<(usize, bool)> {
+    // A tip is a unitig with a dead end on one side and a branching node,
+    // with other paths through it, on the other
+    //
+    // # Returns
+    // The branching node of a tip and whether the tip leaves it (true) or
+    // enters it (false), or None if the unitig is not a tip
+    let start = graph.edges[unitig.edges[0]].from;
+    let end = graph.edges[*unitig.edges.last().unwrap()].to;
+
+    if graph.out_edges(end).is_empty() && graph.out_edges(start).len() > 1 {
+        Some((start, true))
+    } else if graph.in_edges(start).is_empty() && graph.in_edges(end).len() > 1 {
+        Some((end, false))
+    } else {
+        None
+    }
+}
+
+fn clip_tips(graph: &mut DeBruijnGraph, tip_length: usize) -> (usize, usize) {
+    // Remove the tips shorter than `tip_length` bases. A tip is only clipped
+    // if another branch on the same side of its branching node is at least
+    // as long, the first one in edge order winning ties, so that one of
+    // several short branches always survives. Clipping a tip may turn its
+    // neighbours into new tips, so the pass is repeated until no more are
+    // found
+    //
+    // # Returns
+    // The number of tips and of edges removed
+    let (mut tips, mut edges) = (0, 0);
+    loop {
+        let unitigs = find_unitigs(graph);
+        let mut branches: HashMap<(usize, bool), Vec<usize>> = HashMap::new();
+        for (i, unitig) in unitigs.iter().enumerate() {
+            let start = graph.edges[unitig.edges[0]].from;
+            let end = graph.edges[*unitig.edges.last().unwrap()].to;
+            branches.entry((start, true)).or_default().push(i);
+            branches.entry((end, false)).or_default().push(i);
+        }
+
+        let mut removed = vec![false; graph.edges.len()];
+        let mut clipped = 0;
+        for (i, unitig) in unitigs.iter().enumerate() {
+            if unitig.seq.len() >= tip_length {
+                continue;
+            }
+            let Some(branch_point) = tip_branch_point(graph, unitig) else {
+                continue;
+            };
+            let longest = branches[&branch_point]
+                .iter()
+                .max_by_key(|&&j| (unitigs[j].seq.len(), Reverse(j)))
+                .unwrap();
+            if *longest != i {
+                clipped += 1;
+                for &e in &unitig.edges {
+                    removed[e] = true;
+                }
+            }
+        }
+        if clipped == 0 {
+            return (tips, edges);
+        }
+        tips += clipped;
+        edges += removed.iter().filter(|&&r| r).count();
+        graph.remove_edges(&removed);
+    }
+}
+
+pub fn find_bubbles(graph: &DeBruijnGraph) -> Vec<Vec<Unitig>> {
+    // Find the bubbles of the graph: groups of two or more unitigs that leave
+    // and rejoin the same two nodes
+    //
+    // # Arguments
+    // * `graph` - The de Bruijn graph
+    //
+    // # Returns
+    // A vector with the branches of each bubble, ordered by their start and
+    // end nodes
+    let mut bubbles: HashMap<(usize, usize), Vec<Unitig>> = HashMap::new();
+    for unitig in find_unitigs(graph) {
+        let start = graph.edges[unitig.edges[0]].from;
+        let end = graph.edges[*unitig.edges.last().unwrap()].to;
+        if start != end {
+            bubbles.entry((start, end)).or_default().push(unitig);
+        }
+    }
+
+    let mut bubbles: Vec<((usize, usize), Vec<Unitig>)> = bubbles
+        .into_iter()
+        .filter(|(_, branches)| branches.len() > 1)
+        .collect();
+    bubbles.sort_by_key(|(ends, _)| *ends);
+    bubbles.into_iter().map(|(_, branches)| branches).collect()
+}
+
+fn pop_bubbles(graph: &mut DeBruijnGraph, bubble_length: usize) -> (usize, usize) {
+    // Pop the bubbles of the graph. Of each bubble only the branch with the
+    // highest coverage is kept, and the other branches are removed if they
+    // are at most `bubble_length` bases long
+    //
+    // # Returns
+    // The number of branches and of edges removed
+    let mut removed = vec![false; graph.edges.len()];
+    let mut branches = 0;
+    for mut branches_of_bubble in find_bubbles(graph) {
+        branches_of_bubble.sort_by(|a, b| {
+            b.coverage(graph)
+                .total_cmp(&a.coverage(graph))
+                .then(a.edges[0].cmp(&b.edges[0]))
+        });
+        for unitig in branches_of_bubble.iter().skip(1) {
+            if unitig.seq.len() <= bubble_length {
+                branches += 1;
+                for &e in &unitig.edges {
+                    removed[e] = true;
+                }
+            }
+        }
+    }
+    graph.remove_edges(&removed);
+
+    (branches, removed.iter().filter(|&&r| r).count())
+}
+
+pub fn simplify_graph(graph: &mut DeBruijnGraph, args: &SimplifyArgs) {
+    // Run the simplification passes selected in `args` on the graph, in
+    // order: low coverage edges, tips and bubbles. Each pass logs what it
+    // removed to stderr
+    //
+    // # Arguments
+    // * `graph` - The de Bruijn graph, simplified in place
+    // * `args` - The thresholds of the passes. Passes without one are skipped
+    if let Some(min_coverage) = args.min_coverage {
+        let edges = remove_low_coverage_edges(graph, min_coverage);
+        eprintln!(
+            "Removed {} edges with coverage below {}",
+            edges, min_coverage
+        );
+    }
+    if let Some(tip_length) = args.tip_length {
+        let (tips, edges) = clip_tips(graph, tip_length);
+        eprintln!("Clipped {} tips ({} edges)", tips, edges);
+    }
+    if let Some(bubble_length) = args.bubble_length {
+        let (branches, edges) = pop_bubbles(graph, bubble_length);
+        eprintln!("Popped {} bubble branches ({} edges)", branches, edges);
+    }
+}
@@ -0,0 +1,244 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use crate::graph_format::GraphFormat;
+use crate::nucleotide::{is_rna, reverse_complement};
+use crate::overlap::{
+    check_records, generate_dot_output, generate_formated_output, generate_gfa_output,
+    generate_paf_output, mk_overlap_graph, Overlap, OverlapOptions,
+};
+use crate::Fasta;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    Vacant,
+    InPlay,
+    Eliminated,
+}
+
+fn find_contained(fastas: &[Fasta], rc: bool) -> Result<Vec<bool>, String> {
+    // Find the reads that are fully contained in another read. Of a group of
+    // identical reads only the first one is kept. Containment is exact, even
+    // when overlaps allow errors, so a read contained with errors is kept
+    //
+    // # Arguments
+    // * `fastas` - A slice of Fasta structs
+    // * `rc` - Whether to also look for the reverse complement of each read
+    //
+    // # Returns
+    // A vector indicating, for each read, whether it is contained in another one
+    let mut contained = vec![false; fastas.len()];
+    let rna = is_rna(fastas.iter().map(|fasta| fasta.seq.as_str()));
+
+    for (j, fasta_j) in fastas.iter().enumerate() {
+        let mut patterns = vec![fasta_j.seq.clone()];
+        if rc {
+            patterns.push(reverse_complement(&fasta_j.seq, rna)?);
+        }
+
+        for (i, fasta_i) in fastas.iter().enumerate() {
+            if i == j || contained[i] {
+                continue;
+            }
+            let longer = fasta_i.seq.len() > fasta_j.seq.len();
+            let earlier_duplicate = fasta_i.seq.len() == fasta_j.seq.len() && i < j;
+
+            if (longer || earlier_duplicate)
+                && patterns.iter().any(|p| fasta_i.seq.contains(p.as_str()))
+            {
+                contained[j] = true;
+                break;
+            }
+        }
+    }
+    Ok(contained)
+}
+
+fn overhang(overlap: &Overlap, fastas: &[Fasta]) -> usize {
+    // Number of bases of the target that extend past the end of the source
+    fastas[overlap.target].seq.len() - overlap.target_span
+}
+
+fn reduce_transitive_edges(graph: Vec<Overlap>, fastas: &[Fasta], fuzz: usize) -> Vec<Overlap> {
+    // Remove the edges that can be inferred from two other edges, following
+    // Myers' transitive reduction of the string graph. Vertices are oriented
+    // reads, so that reverse complement edges are reduced independently
+    //
+    // # Arguments
+    // * `graph` - A vector with the overlaps between the reads
+    // * `fastas` - The records the overlap indices refer to
+    // * `fuzz` - Slack, in bases, allowed when comparing overhang lengths
+    //
+    // # Returns
+    // A vector with the overlaps that are not transitively inferable
+    let mut out_edges: HashMap<(usize, char), Vec<usize>> = HashMap::new();
+    for (e, overlap) in graph.iter().enumerate() {
+        out_edges
+            .entry((overlap.source, overlap.source_strand))
+            .or_default()
+            .push(e);
+    }
+    for edges in out_edges.values_mut() {
+        edges.sort_by_key(|&e| (overhang(&graph[e], fastas), e));
+    }
+
+    let target = |e: usize| (graph[e].target, graph[e].target_strand);
+    let mut marks: HashMap<(usize, char), Mark> = HashMap::new();
+    let mut reduced = vec![false; graph.len()];
+
+    let mut vertices: Vec<&(usize, char)> = out_edges.keys().collect();
+    vertices.sort();
+
+    for v in vertices {
+        let edges = &out_edges[v];
+        for &e in edges {
+            marks.insert(target(e), Mark::InPlay);
+        }
+        let longest = overhang(&graph[*edges.last().unwrap()], fastas) + fuzz;
+
+        for &e in edges {
+            let w = target(e);
+            if marks[&w] != Mark::InPlay {
+                continue;
+            }
+            let hang_vw = overhang(&graph[e], fastas);
+            for &f in out_edges.get(&w).map(|f| f.as_slice()).unwrap_or(&[]) {
+                if hang_vw + overhang(&graph[f], fastas) > longest {
+                    break;
+                }
+                if marks.get(&target(f)) == Some(&Mark::InPlay) {
+                    marks.insert(target(f), Mark::Eliminated);
+                }
+            }
+        }
+        for &e in edges {
+            let w = target(e);
+            for (n, &f) in out_edges
+                .get(&w)
+                .map(|f| f.as_slice())
+                .unwrap_or(&[])
+                .iter()
+                .enumerate()
+            {
+                if n > 0 && overhang(&graph[f], fastas) >= fuzz {
+                    break;
+                }
+                if marks.get(&target(f)) == Some(&Mark::InPlay) {
+                    marks.insert(target(f), Mark::Eliminated);
+                }
+            }
+        }
+        for &e in edges {
+            if marks[&target(e)] == Mark::Eliminated {
+                reduced[e] = true;
+            }
+            marks.insert(target(e), Mark::Vacant);
+        }
+    }
+
+    graph
+        .into_iter()
+        .zip(reduced)
+        .filter(|(_, reduced)| !reduced)
+        .map(|(overlap, _)| overlap)
+        .collect()
+}
+
+pub fn run_string_graph(
+    input: Vec<Fasta>,
+    mut options: OverlapOptions,
+    fuzz: usize,
+    format: GraphFormat,
+    collapse_labels: bool,
+    paf: bool,
+) -> Result<String, String> {
+    // Build the string graph of a set of reads: contained reads are removed,
+    // proper overlaps of at least `k` bases are computed and transitively
+    // inferable edges are reduced
+    //
+    // # Arguments
+    // * `input` - A vector of Fasta structs
+    // * `options` - The overlap options. Without an error bound, the longest
+    //               exact overlap of each pair is used
+    // * `fuzz` - Slack, in bases, allowed when comparing overhang lengths
+    //            during the transitive reduction
+    // * `format` - The output format
+    // * `collapse_labels` - Whether to draw unlabelled nodes in DOT output
+    // * `paf` - Write the overlaps as PAF records instead of the graph
+    //
+    // # Returns
+    // Result containing a string with the formated output or an error message
+    if input.is_empty() {
+        return Err(String::from("No input sequences"));
+    }
+    check_records(&input, options.k)?;
+    // Without an error bound, overlaps of any length from `k` are reported,
+    // and the longest exact one is computed directly
+    if !options.is_approximate() {
+        options.max_errors = Some(0);
+    }
+
+    let contained = find_contained(&input, options.rc)?;
+    eprintln!(
+        "Removed {} contained reads",
+        contained.iter().filter(|&&c| c).count()
+    );
+
+    let mut graph = mk_overlap_graph(&input, &options)?;
+    graph.retain(|overlap| {
+        !contained[overlap.source]
+            && !contained[overlap.target]
+            && overlap.source_span < input[overlap.source].seq.len()
+            && overlap.target_span < input[overlap.target].seq.len()
+    });
+
+    // A chance match can give a pair of reads a second edge in another
+    // direction or orientation, so only the best overlap of each pair is kept
+    let mut best: HashMap<(usize, usize), usize> = HashMap::new();
+    let score = |overlap: &Overlap| (overlap.matches, Reverse(overlap.block_len));
+    for (i, overlap) in graph.iter().enumerate() {
+        let pair = (
+            overlap.source.min(overlap.target),
+            overlap.source.max(overlap.target),
+        );
+        let kept = best.entry(pair).or_insert(i);
+        if score(overlap) > score(&graph[*kept]) {
+            *kept = i;
+        }
+    }
+    let kept: HashSet<usize> = best.into_values().collect();
+    let mut graph: Vec<Overlap> = graph
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| kept.contains(i))
+        .map(|(_, overlap)| overlap)
+        .collect();
+
+    // Oriented reads need their edges on both strands for the reduction, and
+    // only the overlaps reported by `mk_overlap_graph` are kept afterwards
+    let total_edges = graph.len();
+    if options.rc {
+        let mirrors: Vec<Overlap> = graph.iter().map(Overlap::mirror).collect();
+        graph.extend(mirrors);
+    }
+    let mut graph = reduce_transitive_edges(graph, &input, fuzz);
+    if options.rc {
+        graph.retain(|overlap| overlap.source < overlap.target);
+    }
+    eprintln!("Removed {} transitive edges", total_edges - graph.len());
+
+    let segments: Vec<usize> = (0..input.len()).filter(|&i| !contained[i]).collect();
+    if paf {
+        return Ok(generate_paf_output(graph, &input));
+    }
+    match format {
+        GraphFormat::Text => Ok(generate_formated_output(graph, &input, &options)),
+        GraphFormat::Gfa => generate_gfa_output(graph, &input, &segments),
+        GraphFormat::Dot => Ok(generate_dot_output(
+            graph,
+            &input,
+            &segments,
+            collapse_labels,
+        )),
+    }
+}
@@ -1,23 +1,45 @@
-use std::cmp::min;
-use std::collections::HashMap;
+use std::cmp::{min, Reverse};
+use std::collections::{BTreeSet, BinaryHeap};
+
+use clap::ValueEnum;
 
 use crate::Fasta;
 
-fn obtain_sequence_list(fastas: Vec<Fasta>) -> Vec<String> {
+// Largest number of sequences for which the exact solver is used
+const EXACT_LIMIT: usize = 20;
+
+// Number of optimal paths followed for each superstring requested with
+// `--all`. Many paths can spell the same superstring, so the search is bounded
+// by the paths followed and not only by the superstrings found
+const PATHS_PER_SUPERSTRING: usize = 100;
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum LayoutFormat {
+    /// Tab-separated ID, start, end and overlap with the previous read
+    Tsv,
+    /// BED intervals on the superstring
+    Bed,
+}
+
+fn obtain_sequence_list(fastas: Vec<Fasta>) -> Result<Vec<String>, &'static str> {
     // Obtain a list of sequences from a list of FASTA objects
     //
     // # Arguments
     // * `fastas` - A vector of FASTA objects
     //
     // # Returns
-    // A vector of sequences
+    // A vector of sequences, or an error if one of them is not ASCII, as
+    // overlaps are compared byte by byte
     let mut sequences: Vec<String> = Vec::new();
 
     for fasta in fastas {
+        if !fasta.seq.is_ascii() {
+            return Err("Error: sequences can only contain ASCII characters");
+        }
         sequences.push(fasta.seq);
     }
 
-    sequences
+    Ok(sequences)
 }
 
 fn get_min_suffix(seq1: &str, seq2: &str) -> String {
@@ -40,126 +62,792 @@ fn get_min_suffix(seq1: &str, seq2: &str) -> String {
     seq2.to_string() // no non-overlapping suffix found
 }
 
-// TODO: Handle situation where there are multiple valid next nodes
-fn find_next_node(
-    overlaps: &HashMap<usize, HashMap<usize, i32>>,
-    node: usize,
-) -> Result<usize, &'static str> {
-    // Find the next node in the path
+struct Placement {
+    // Struct to hold the position of an input sequence in one of the contigs
+    record: usize,
+    contig: usize,
+    start: usize,
+    end: usize,
+}
+
+// Marker for trie nodes that don't end any sequence
+const NO_READ: u32 = u32::MAX;
+
+struct AhoCorasick {
+    // Aho-Corasick automaton over all the sequences. Children are stored in a
+    // flat vector with one slot per symbol, and once the failure links are built
+    // they hold the full goto function. The sequences are ranked in
+    // lexicographic order, so the sequences starting with the string spelled by
+    // a node are the ones with rank in `range[node]`
+    symbols: [u8; 256],
+    sigma: usize,
+    children: Vec<u32>,
+    fail: Vec<u32>,
+    depth: Vec<u32>,
+    terminal: Vec<u32>,
+    range: Vec<(u32, u32)>,
+    ranked: Vec<u32>,
+}
+
+impl AhoCorasick {
+    fn new(sequences: &[String]) -> Self {
+        // Build the automaton of a list of sequences
+        //
+        // # Arguments
+        // * `sequences` - A vector of sequences
+        //
+        // # Returns
+        // The automaton, with one terminal node per distinct sequence
+        let mut present = [false; 256];
+        for seq in sequences {
+            for &b in seq.as_bytes() {
+                present[b as usize] = true;
+            }
+        }
+        let mut symbols = [0u8; 256];
+        let mut sigma = 0;
+        for b in 0..256 {
+            if present[b] {
+                symbols[b] = sigma as u8;
+                sigma += 1;
+            }
+        }
+        let sigma = sigma.max(1);
+
+        let mut ac = Self {
+            symbols,
+            sigma,
+            children: vec![0; sigma],
+            fail: vec![0],
+            depth: vec![0],
+            terminal: vec![NO_READ],
+            range: Vec::new(),
+            ranked: Vec::new(),
+        };
+
+        for (i, seq) in sequences.iter().enumerate() {
+            let mut node = 0;
+            for &b in seq.as_bytes() {
+                let slot = node * sigma + ac.symbols[b as usize] as usize;
+                if ac.children[slot] == 0 {
+                    ac.children[slot] = ac.depth.len() as u32;
+                    ac.children.resize(ac.children.len() + sigma, 0);
+                    ac.fail.push(0);
+                    ac.depth.push(ac.depth[node] + 1);
+                    ac.terminal.push(NO_READ);
+                }
+                node = ac.children[slot] as usize;
+            }
+            if ac.terminal[node] == NO_READ {
+                ac.terminal[node] = i as u32;
+            }
+        }
+
+        ac.rank_sequences();
+        ac.build_failure_links();
+        ac
+    }
+
+    fn rank_sequences(&mut self) {
+        // Rank the terminal nodes in lexicographic order with a depth-first
+        // traversal of the trie, and store the range of ranks below each node
+        self.range = vec![(0, 0); self.depth.len()];
+        let mut stack = vec![(0usize, 0usize)];
+        self.range[0].0 = 0;
+        if self.terminal[0] != NO_READ {
+            self.ranked.push(self.terminal[0]);
+        }
+
+        while let Some((node, symbol)) = stack.pop() {
+            if symbol == self.sigma {
+                self.range[node].1 = self.ranked.len() as u32;
+                continue;
+            }
+            stack.push((node, symbol + 1));
+
+            let child = self.children[node * self.sigma + symbol] as usize;
+            if child != 0 {
+                self.range[child].0 = self.ranked.len() as u32;
+                if self.terminal[child] != NO_READ {
+                    self.ranked.push(self.terminal[child]);
+                }
+                stack.push((child, 0));
+            }
+        }
+    }
+
+    fn build_failure_links(&mut self) {
+        // Compute the failure links in breadth-first order and complete the
+        // goto function with them
+        let sigma = self.sigma;
+        let mut queue = std::collections::VecDeque::new();
+
+        for symbol in 0..sigma {
+            let child = self.children[symbol];
+            if child != 0 {
+                queue.push_back(child as usize);
+            }
+        }
+        while let Some(node) = queue.pop_front() {
+            let fail = self.fail[node] as usize;
+            for symbol in 0..sigma {
+                let child = self.children[node * sigma + symbol];
+                if child != 0 {
+                    self.fail[child as usize] = self.children[fail * sigma + symbol];
+                    queue.push_back(child as usize);
+                } else {
+                    self.children[node * sigma + symbol] = self.children[fail * sigma + symbol];
+                }
+            }
+        }
+    }
+
+    fn scan<'a>(&'a self, seq: &'a str) -> impl Iterator<Item = usize> + 'a {
+        // Feed a sequence to the automaton
+        //
+        // # Arguments
+        // * `seq` - A sequence made of symbols known to the automaton
+        //
+        // # Returns
+        // An iterator over the state reached after each position
+        seq.bytes().scan(0usize, move |node, b| {
+            *node = self.children[*node * self.sigma + self.symbols[b as usize] as usize] as usize;
+            Some(*node)
+        })
+    }
+}
+
+fn find_contained(sequences: &[String], ac: &AhoCorasick) -> Vec<Option<(usize, usize)>> {
+    // Find the sequences that are a substring of another sequence, keeping
+    // the first copy of repeated sequences. Every trie node reached while
+    // scanning a sequence, or along its failure links, spells a substring of
+    // that sequence. Each node is only walked once
     //
     // # Arguments
-    // * `overlaps` - A hashmap of hashmaps containing the overlaps between sequences
-    // * `node` - The current Node
+    // * `sequences` - A vector of sequences
+    // * `ac` - The automaton of the sequences
     //
     // # Returns
-    // The next node in the path
-    //
-    // # Errors
-    // If the node is not found in the overlaps hashmap, an error is returned.
+    // A vector with, for each contained sequence, the index of a sequence that
+    // contains it and the offset where it starts in that sequence
+    let mut containers: Vec<Option<(usize, usize)>> = vec![None; sequences.len()];
+    let mut visited = vec![false; ac.depth.len()];
+
+    // Repeated sequences share a node, which only ends the first copy
+    for (i, seq) in sequences.iter().enumerate() {
+        let end = ac.scan(seq).last().unwrap_or(0);
+        if ac.terminal[end] != i as u32 {
+            containers[i] = Some((ac.terminal[end] as usize, 0));
+        }
+    }
 
-    // Attempt to get the sub-hashmap for the node. If it doesn't exist, return an error.
-    let sub_map = overlaps
-        .get(&node)
-        .ok_or("Error: Node not found in overlaps")?;
+    for (i, seq) in sequences.iter().enumerate() {
+        if containers[i].is_some() {
+            continue;
+        }
+        for (pos, state) in ac.scan(seq).enumerate() {
+            // A sequence ending at its own node is not contained in itself
+            let mut node = if pos + 1 == seq.len() {
+                ac.fail[state] as usize
+            } else {
+                state
+            };
+            while node != 0 && !visited[node] {
+                visited[node] = true;
+                if ac.terminal[node] != NO_READ {
+                    let offset = pos + 1 - ac.depth[node] as usize;
+                    containers[ac.terminal[node] as usize] = Some((i, offset));
+                }
+                node = ac.fail[node] as usize;
+            }
+        }
+    }
+    containers
+}
 
-    // Proceed to find the minimum, ensuring there's at least one valid entry.
-    match sub_map
-        .iter()
-        .filter(|(&_j, &value)| value != -1) // Filter out values of '-1'.
-        .min_by_key(|&(_, &value)| value)
-    {
-        // Find the minimum by value.
-        Some((&j, _)) => Ok(j), // If found, return the index as Ok.
-        None => Err("Error: No valid next node found"), // If not found, return an error.
+fn find_chain_head(chains: &mut [u32], mut i: usize) -> usize {
+    // Find the representative of the chain of merged sequences containing `i`
+    while chains[i] as usize != i {
+        chains[i] = chains[chains[i] as usize];
+        i = chains[i] as usize;
     }
+    i
+}
+
+struct MinTree {
+    // Segment tree answering range minimum queries over the input index of
+    // the sequences, stored by rank. Unavailable sequences hold `NO_READ`
+    size: usize,
+    values: Vec<u32>,
 }
 
-pub fn shortest_superstring(sequences: Vec<String>) -> Result<String, &'static str> {
-    // Find the shortest superstring of a list of sequences
+impl MinTree {
+    fn new(leaves: Vec<u32>) -> Self {
+        let size = leaves.len().max(1);
+        let mut values = vec![NO_READ; 2 * size];
+        values[size..size + leaves.len()].copy_from_slice(&leaves);
+        for i in (1..size).rev() {
+            values[i] = values[2 * i].min(values[2 * i + 1]);
+        }
+        Self { size, values }
+    }
+
+    fn remove(&mut self, position: usize) {
+        let mut i = position + self.size;
+        self.values[i] = NO_READ;
+        while i > 1 {
+            i /= 2;
+            self.values[i] = self.values[2 * i].min(self.values[2 * i + 1]);
+        }
+    }
+
+    fn min(&self, lo: usize, hi: usize) -> u32 {
+        // Smallest value in the positions `lo..hi`
+        let mut result = NO_READ;
+        let (mut lo, mut hi) = (lo + self.size, hi + self.size);
+        while lo < hi {
+            if lo % 2 == 1 {
+                result = result.min(self.values[lo]);
+                lo += 1;
+            }
+            if hi % 2 == 1 {
+                hi -= 1;
+                result = result.min(self.values[hi]);
+            }
+            lo /= 2;
+            hi /= 2;
+        }
+        result
+    }
+}
+
+fn next_greedy_candidate(
+    ac: &AhoCorasick,
+    cursor: &mut usize,
+    available: &MinTree,
+    rank_of: &[u32],
+    chains: &mut [u32],
+    i: usize,
+    min_overlap: usize,
+) -> Option<(u32, usize)> {
+    // Find the next sequence that the suffix of `i` overlaps, in order of
+    // decreasing overlap length. Ties are broken by input order. `cursor`
+    // holds the current node along the failure links of `i`
+    //
+    // # Arguments
+    // * `ac` - The automaton of the sequences
+    // * `cursor` - The position of the search for `i`, updated in place
+    // * `available` - The sequences that have no predecessor yet, by rank
+    // * `rank_of` - The rank of each sequence
+    // * `chains` - The chains of merged sequences
+    // * `i` - The sequence whose successor is searched
+    // * `min_overlap` - The minimum overlap length, at least 1
+    //
+    // # Returns
+    // The overlap length and the index of the next candidate, if any
+    while *cursor != 0 && ac.depth[*cursor] as usize >= min_overlap {
+        let node = *cursor;
+        let (lo, hi) = (ac.range[node].0 as usize, ac.range[node].1 as usize);
+
+        let mut j = available.min(lo, hi);
+        if j != NO_READ && find_chain_head(chains, j as usize) == find_chain_head(chains, i) {
+            // Merging with the first sequence of its own chain would close a cycle
+            let r = rank_of[j as usize] as usize;
+            j = available.min(lo, r).min(available.min(r + 1, hi));
+        }
+        if j != NO_READ {
+            return Some((ac.depth[node], j as usize));
+        }
+        *cursor = ac.fail[node] as usize;
+    }
+    None
+}
+
+fn greedy_merge_order(
+    sequences: &[String],
+    ac: &AhoCorasick,
+    contained: &[bool],
+    min_overlap: usize,
+) -> Vec<Vec<(usize, usize)>> {
+    // Build a short superstring by repeatedly merging the pair of sequences
+    // with the longest overlap, as long as it doesn't close a cycle. Ties are
+    // broken by the input order of the first and then the second sequence. Overlaps
+    // are found lazily along the failure links of the automaton, so memory
+    // stays linear in the total length of the sequences
     //
     // # Arguments
     // * `sequences` - A vector of sequences
+    // * `ac` - The automaton of the sequences
+    // * `contained` - Whether each sequence is contained in another one
+    // * `min_overlap` - The minimum overlap length of a merge, at least 1
     //
     // # Returns
-    // The shortest superstring of the sequences
+    // The chains of merged sequences, in the order of the sequences in a
+    // short superstring. Each sequence comes with its overlap with the
+    // previous one
     let n = sequences.len();
-    let mut overlaps_len = HashMap::new();
-    let mut overlaps = HashMap::new();
+    let mut chains: Vec<u32> = (0..n as u32).collect();
+    let mut successor: Vec<Option<(usize, usize)>> = vec![None; n];
+    let mut has_predecessor = vec![false; n];
+
+    let mut rank_of = vec![0u32; n];
+    for (r, &i) in ac.ranked.iter().enumerate() {
+        rank_of[i as usize] = r as u32;
+    }
+    let mut available = MinTree::new(
+        ac.ranked
+            .iter()
+            .map(|&i| if contained[i as usize] { NO_READ } else { i })
+            .collect(),
+    );
 
+    let mut cursors = vec![0usize; n];
+    let mut heap = BinaryHeap::new();
+
+    for (i, seq) in sequences.iter().enumerate() {
+        if contained[i] {
+            continue;
+        }
+        // The state after the whole sequence is its own node, so the
+        // candidates start at the longest proper suffix
+        let end = ac.scan(seq).last().unwrap_or(0);
+        cursors[i] = ac.fail[end] as usize;
+        if let Some((len, j)) = next_greedy_candidate(
+            ac,
+            &mut cursors[i],
+            &available,
+            &rank_of,
+            &mut chains,
+            i,
+            min_overlap,
+        ) {
+            heap.push((len, Reverse(i), Reverse(j)));
+        }
+    }
+
+    while let Some((len, Reverse(i), Reverse(j))) = heap.pop() {
+        let valid = !has_predecessor[j]
+            && find_chain_head(&mut chains, i) != find_chain_head(&mut chains, j);
+
+        if valid {
+            successor[i] = Some((j, len as usize));
+            has_predecessor[j] = true;
+            available.remove(rank_of[j] as usize);
+            let head = find_chain_head(&mut chains, j);
+            chains[head] = find_chain_head(&mut chains, i) as u32;
+        } else if let Some((len, j)) = next_greedy_candidate(
+            ac,
+            &mut cursors[i],
+            &available,
+            &rank_of,
+            &mut chains,
+            i,
+            min_overlap,
+        ) {
+            heap.push((len, Reverse(i), Reverse(j)));
+        }
+    }
+
+    // Follow each chain from its first sequence, in input order
+    let mut order = Vec::new();
+    for start in (0..n).filter(|&i| !contained[i] && !has_predecessor[i]) {
+        let mut chain = vec![(start, 0)];
+        let mut current = start;
+        while let Some((next, len)) = successor[current] {
+            chain.push((next, len));
+            current = next;
+        }
+        order.push(chain);
+    }
+    order
+}
+
+fn get_overlap_len(seq1: &str, seq2: &str) -> usize {
+    // Get the length of the longest suffix of seq1 that is a prefix of seq2
+    seq2.len() - get_min_suffix(seq1, seq2).len()
+}
+
+fn held_karp_table(sequences: &[String]) -> (Vec<Vec<i32>>, Vec<i32>) {
+    // Fill the Held-Karp dynamic programming table. `dp[mask * n + j]` holds
+    // the largest total overlap of a path that starts at `j` and visits the
+    // sequences in `mask`. The superstring of the best full path is provably
+    // the shortest one
+    //
+    // # Arguments
+    // * `sequences` - A vector of sequences, none of them contained in another
+    //
+    // # Returns
+    // The overlap between every pair of sequences and the table
+    let n = sequences.len();
+    let full = (1usize << n) - 1;
+
+    let mut overlaps = vec![vec![0; n]; n];
     for i in 0..n {
-        overlaps_len.insert(i, HashMap::new());
-        overlaps.insert(i, HashMap::new());
         for j in 0..n {
             if i != j {
-                overlaps_len
-                    .get_mut(&i)
-                    .unwrap()
-                    .insert(j, get_min_suffix(&sequences[i], &sequences[j]).len() as i32);
-                overlaps
-                    .get_mut(&i)
-                    .unwrap()
-                    .insert(j, get_min_suffix(&sequences[i], &sequences[j]));
-            } else {
-                overlaps_len.get_mut(&i).unwrap().insert(j, -1);
+                overlaps[i][j] = get_overlap_len(&sequences[i], &sequences[j]) as i32;
             }
         }
     }
 
-    let mut candidates = Vec::new();
+    let mut dp = vec![-1i32; (full + 1) * n];
+    for mask in 1..=full {
+        for j in (0..n).filter(|&j| mask & (1 << j) != 0) {
+            let rest = mask ^ (1 << j);
+            dp[mask * n + j] = if rest == 0 {
+                0
+            } else {
+                (0..n)
+                    .filter(|&k| rest & (1 << k) != 0)
+                    .map(|k| overlaps[j][k] + dp[rest * n + k])
+                    .max()
+                    .unwrap()
+            };
+        }
+    }
+    (overlaps, dp)
+}
 
-    for i in 0..n {
-        let mut visited = vec![false; n];
-        let mut path = vec![i];
-        visited[i] = true;
+fn held_karp_order(sequences: &[String]) -> Vec<(usize, usize)> {
+    // Find the order of the sequences in the shortest superstring. Among the
+    // optimal paths, the one that comes first in input order is chosen
+    //
+    // # Arguments
+    // * `sequences` - A vector of sequences, none of them contained in another
+    //
+    // # Returns
+    // The order of the sequences in the shortest superstring, each with its
+    // overlap with the previous one
+    let n = sequences.len();
+    let (overlaps, dp) = held_karp_table(sequences);
+
+    let mut mask = (1usize << n) - 1;
+    let mut current = (0..n)
+        .max_by_key(|&j| (dp[mask * n + j], Reverse(j)))
+        .unwrap();
+    let mut order = vec![(current, 0)];
+
+    while mask != 1 << current {
+        let rest = mask ^ (1 << current);
+        let next = (0..n)
+            .find(|&k| {
+                rest & (1 << k) != 0
+                    && overlaps[current][k] + dp[rest * n + k] == dp[mask * n + current]
+            })
+            .unwrap();
+        order.push((next, overlaps[current][next] as usize));
+        mask = rest;
+        current = next;
+    }
+    order
+}
+
+fn enumerate_optimal_superstrings(sequences: &[String], limit: usize) -> (Vec<String>, bool) {
+    // Enumerate the distinct shortest superstrings by following the optimal
+    // paths of the Held-Karp table, stopping once `limit` strings are found or
+    // `PATHS_PER_SUPERSTRING` paths per requested string have been followed
+    //
+    // # Arguments
+    // * `sequences` - A vector of sequences, none of them contained in another
+    // * `limit` - The maximum number of superstrings to return
+    //
+    // # Returns
+    // The shortest superstrings, sorted alphabetically, and whether the
+    // search ran out of paths to follow before finding `limit` of them
+    let n = sequences.len();
+    let (overlaps, dp) = held_karp_table(sequences);
+    let full = (1usize << n) - 1;
+    let best = (0..n).map(|j| dp[full * n + j]).max().unwrap();
 
-        let mut next_node = find_next_node(&overlaps_len, (i as i32).try_into().unwrap())?;
+    let mut found = BTreeSet::new();
+    let mut stack: Vec<(usize, usize, String)> = (0..n)
+        .rev()
+        .filter(|&j| dp[full * n + j] == best)
+        .map(|j| (full, j, sequences[j].clone()))
+        .collect();
+    let max_paths = limit.saturating_mul(PATHS_PER_SUPERSTRING);
+    let mut paths = 0;
 
-        while !visited[next_node as usize] {
-            path.push(next_node as usize);
-            visited[next_node as usize] = true;
-            next_node = find_next_node(&overlaps_len, next_node)?;
+    while found.len() < limit && paths < max_paths {
+        let Some((mask, current, sstring)) = stack.pop() else {
+            break;
+        };
+        if mask == 1 << current {
+            found.insert(sstring);
+            paths += 1;
+            continue;
         }
+        let rest = mask ^ (1 << current);
+        for k in (0..n).rev() {
+            if rest & (1 << k) != 0
+                && overlaps[current][k] + dp[rest * n + k] == dp[mask * n + current]
+            {
+                let next = sstring.clone() + &sequences[k][overlaps[current][k] as usize..];
+                stack.push((rest, k, next));
+            }
+        }
+    }
+    let truncated = found.len() < limit && !stack.is_empty();
+    (found.into_iter().collect(), truncated)
+}
 
-        if path.len() == n {
-            candidates.push(path);
+fn place_sequences(
+    sequences: &[String],
+    contigs: &[Vec<(usize, usize)>],
+    containers: &[Option<(usize, usize)>],
+) -> (Vec<String>, Vec<Placement>) {
+    // Spell the contigs made of the sequences in `contigs` and place every
+    // sequence on them. Contained sequences are placed relative to the
+    // sequence that contains them
+    //
+    // # Arguments
+    // * `sequences` - A vector of sequences
+    // * `contigs` - The sequences in each contig, each with its overlap with
+    //               the previous one
+    // * `containers` - The container and offset of each contained sequence
+    //
+    // # Returns
+    // The contigs and the placement of each sequence, in input order
+    let mut spelled = Vec::new();
+    let mut starts: Vec<Option<(usize, usize)>> = vec![None; sequences.len()];
+
+    for (c, contig) in contigs.iter().enumerate() {
+        let mut sstring = String::new();
+        for &(i, overlap) in contig {
+            starts[i] = Some((c, sstring.len() - overlap));
+            sstring.push_str(&sequences[i][overlap..]);
         }
+        spelled.push(sstring);
     }
 
-    let mut superstrings = Vec::new();
+    let mut placements = Vec::new();
+    for (i, seq) in sequences.iter().enumerate() {
+        let mut start = 0;
+        let mut current = i;
+        while let Some((container, offset)) = containers[current] {
+            start += offset;
+            current = container;
+        }
+        let (contig, offset) = starts[current].unwrap();
+        start += offset;
+        placements.push(Placement {
+            record: i,
+            contig,
+            start,
+            end: start + seq.len(),
+        });
+    }
+    (spelled, placements)
+}
+
+fn assemble_superstring(
+    sequences: &[String],
+    min_overlap: Option<usize>,
+) -> Result<(Vec<String>, Vec<Placement>), &'static str> {
+    // Find the shortest superstring of a list of sequences and place every
+    // sequence on it. Contained sequences are removed first. Up to
+    // `EXACT_LIMIT` sequences the exact solver is used, otherwise the greedy
+    // merge. With a minimum overlap, sequences are only merged when they
+    // overlap by at least that much, and one contig is built per chain of
+    // merged sequences
+    //
+    // # Arguments
+    // * `sequences` - A vector of sequences
+    // * `min_overlap` - The minimum overlap length to split the result in contigs
+    //
+    // # Returns
+    // The superstring or contigs and the placement of each sequence
+    if sequences.is_empty() {
+        return Err("Error: No input sequences");
+    }
+    let ac = AhoCorasick::new(sequences);
+    let containers = find_contained(sequences, &ac);
+    let contained: Vec<bool> = containers.iter().map(|c| c.is_some()).collect();
+
+    let contigs = if let Some(min_overlap) = min_overlap {
+        greedy_merge_order(sequences, &ac, &contained, min_overlap.max(1))
+    } else if contained.iter().filter(|&&c| !c).count() <= EXACT_LIMIT {
+        let kept: Vec<usize> = (0..sequences.len()).filter(|&i| !contained[i]).collect();
+        let kept_sequences: Vec<String> = kept.iter().map(|&i| sequences[i].clone()).collect();
+        let order = held_karp_order(&kept_sequences)
+            .into_iter()
+            .map(|(i, overlap)| (kept[i], overlap))
+            .collect();
+        vec![order]
+    } else {
+        eprintln!(
+            "Warning: more than {} sequences, the superstring may not be the shortest",
+            EXACT_LIMIT
+        );
+        vec![greedy_merge_order(sequences, &ac, &contained, 1).concat()]
+    };
+
+    Ok(place_sequences(sequences, &contigs, &containers))
+}
 
-    for candidate in candidates {
-        let mut sstring = sequences[candidate[0]].clone();
+fn generate_layout_output(
+    headers: &[String],
+    contig_names: &[String],
+    mut placements: Vec<Placement>,
+    format: LayoutFormat,
+) -> String {
+    // Generate the layout of the sequences on the contigs, sorted by contig
+    // and start offset. The TSV output reports the overlap of each sequence
+    // with the previous one in the same contig
+    //
+    // # Arguments
+    // * `headers` - The IDs of the input records
+    // * `contig_names` - The name of each contig
+    // * `placements` - The placement of each sequence
+    // * `format` - The layout format
+    //
+    // # Returns
+    // A string with one line per input record
+    placements.sort_by_key(|p| (p.contig, p.start, p.record));
 
-        for i in 1..candidate.len() {
-            sstring += &overlaps
-                .get(&candidate[i - 1])
-                .unwrap()
-                .get(&candidate[i])
-                .unwrap();
+    let mut output = String::new();
+    if format == LayoutFormat::Tsv {
+        output.push_str("id\tcontig\tstart\tend\toverlap\n");
+    }
+
+    let mut previous: Option<(usize, usize)> = None;
+    for placement in placements {
+        let header = &headers[placement.record];
+        let contig = &contig_names[placement.contig];
+        match format {
+            LayoutFormat::Tsv => {
+                let overlap = match previous {
+                    Some((c, end)) if c == placement.contig => {
+                        end.min(placement.end).saturating_sub(placement.start)
+                    }
+                    _ => 0,
+                };
+                output.push_str(&format!(
+                    "{}\t{}\t{}\t{}\t{}\n",
+                    header, contig, placement.start, placement.end, overlap
+                ));
+            }
+            LayoutFormat::Bed => {
+                output.push_str(&format!(
+                    "{}\t{}\t{}\t{}\n",
+                    contig, placement.start, placement.end, header
+                ));
+            }
         }
-        superstrings.push(sstring);
+        previous = Some((placement.contig, placement.end));
+    }
+    output
+}
+
+fn generate_contigs_output(
+    headers: &[String],
+    contig_names: &[String],
+    contigs: &[String],
+    placements: &[Placement],
+) -> String {
+    // Generate a multi-FASTA output with one record per contig. The header of
+    // each record lists the IDs of the input records placed on the contig
+    //
+    // # Arguments
+    // * `headers` - The IDs of the input records
+    // * `contig_names` - The name of each contig
+    // * `contigs` - The sequence of each contig
+    // * `placements` - The placement of each sequence
+    //
+    // # Returns
+    // A string containing the multi-FASTA output
+    let mut reads: Vec<Vec<&str>> = vec![Vec::new(); contigs.len()];
+    let mut sorted: Vec<&Placement> = placements.iter().collect();
+    sorted.sort_by_key(|p| (p.start, p.record));
+    for placement in sorted {
+        reads[placement.contig].push(&headers[placement.record]);
     }
 
-    let best_superstring = superstrings.iter().min();
-    match best_superstring {
-        Some(x) => Ok(x.to_string()),
-        None => Err("Error: empty list when finding best superstring"),
+    let mut output = String::new();
+    for (c, contig) in contigs.iter().enumerate() {
+        output.push_str(&format!(
+            ">{} len={} reads={}\n{}\n",
+            contig_names[c],
+            contig.len(),
+            reads[c].join(","),
+            contig
+        ));
     }
+
+    output.pop();
+
+    output
 }
 
-pub fn run_superstring(fastas: Vec<Fasta>) -> Result<String, &'static str> {
-    // Run the shortest superstring algorithm
+pub fn run_all_superstrings(fastas: Vec<Fasta>, limit: usize) -> Result<String, &'static str> {
+    // Enumerate the distinct shortest superstrings of the sequences
     //
     // # Arguments
     // * `fastas` - A vector of FASTA structs
+    // * `limit` - The maximum number of superstrings to return
     //
     // # Returns
-    // The shortest superstring of the sequences
-    let sequences = obtain_sequence_list(fastas);
+    // The shortest superstrings, one per line
+    let sequences = obtain_sequence_list(fastas)?;
+    if sequences.is_empty() {
+        return Err("Error: No input sequences");
+    }
+    let ac = AhoCorasick::new(&sequences);
+    let kept: Vec<String> = sequences
+        .iter()
+        .zip(find_contained(&sequences, &ac))
+        .filter(|(_, container)| container.is_none())
+        .map(|(seq, _)| seq.clone())
+        .collect();
 
-    let result = shortest_superstring(sequences);
-    match result {
-        Ok(x) => Ok(x),
-        Err(e) => Err(e),
+    if kept.len() > EXACT_LIMIT {
+        return Err(
+            "Error: all shortest superstrings can only be enumerated for up to 20 sequences",
+        );
+    }
+    let (superstrings, truncated) = enumerate_optimal_superstrings(&kept, limit);
+    if truncated {
+        eprintln!(
+            "Warning: stopped after {} optimal paths, there may be more shortest superstrings",
+            limit.saturating_mul(PATHS_PER_SUPERSTRING)
+        );
     }
+    Ok(superstrings.join("\n"))
+}
+
+pub fn run_superstring(
+    fastas: Vec<Fasta>,
+    layout: Option<LayoutFormat>,
+    min_overlap: Option<usize>,
+) -> Result<(String, Option<String>), &'static str> {
+    // Run the shortest superstring algorithm
+    //
+    // # Arguments
+    // * `fastas` - A vector of FASTA structs
+    // * `layout` - The format of the layout report, if one is requested
+    // * `min_overlap` - The minimum overlap length, to return contigs as
+    //                   multi-FASTA instead of a single superstring
+    //
+    // # Returns
+    // The shortest superstring of the sequences, or the contigs, and the
+    // layout report
+    let headers: Vec<String> = fastas.iter().map(|fasta| fasta.header.clone()).collect();
+    let sequences = obtain_sequence_list(fastas)?;
+
+    let (contigs, placements) = assemble_superstring(&sequences, min_overlap)?;
+    let contig_names: Vec<String> = match min_overlap {
+        Some(_) => (1..=contigs.len())
+            .map(|c| format!("contig_{}", c))
+            .collect(),
+        None => vec![String::from("superstring")],
+    };
+
+    let result = match min_overlap {
+        Some(_) => generate_contigs_output(&headers, &contig_names, &contigs, &placements),
+        None => contigs[0].clone(),
+    };
+    let layout =
+        layout.map(|format| generate_layout_output(&headers, &contig_names, placements, format));
+
+    Ok((result, layout))
 }
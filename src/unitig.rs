@@ -0,0 +1,73 @@
+use crate::debruijn::DeBruijnGraph;
+
+pub struct Unitig {
+    // A maximal non-branching path of the de Bruijn graph
+    pub seq: String,
+    pub edges: Vec<usize>,
+}
+
+impl Unitig {
+    pub fn coverage(&self, graph: &DeBruijnGraph) -> f64 {
+        // Mean number of times the (k+1)-mers of the unitig were seen
+        let total: usize = self.edges.iter().map(|&e| graph.edges[e].count).sum();
+        total as f64 / self.edges.len() as f64
+    }
+}
+
+fn is_one_in_one_out(graph: &DeBruijnGraph, node: usize) -> bool {
+    graph.in_edges(node).len() == 1 && graph.out_edges(node).len() == 1
+}
+
+fn extend_unitig(graph: &DeBruijnGraph, first: usize, used: &mut [bool]) -> Unitig {
+    // Follow the edges from `first` for as long as the path does not branch
+    //
+    // # Arguments
+    // * `graph` - The de Bruijn graph
+    // * `first` - The first edge of the unitig
+    // * `used` - The edges already placed in a unitig, updated in place
+    //
+    // # Returns
+    // The unitig starting with `first`
+    let mut seq = graph.nodes[graph.edges[first].from].clone();
+    let mut edges = Vec::new();
+
+    let mut e = first;
+    while !used[e] {
+        used[e] = true;
+        edges.push(e);
+        let to = graph.edges[e].to;
+        seq.push_str(&graph.nodes[to][graph.nodes[to].len() - 1..]);
+
+        if !is_one_in_one_out(graph, to) {
+            break;
+        }
+        e = graph.out_edges(to)[0];
+    }
+    Unitig { seq, edges }
+}
+
+pub fn find_unitigs(graph: &DeBruijnGraph) -> Vec<Unitig> {
+    // Compact the graph into its maximal non-branching paths. Each unitig
+    // starts at an edge leaving a branching node, or, for cycles made only of
+    // non-branching nodes, at their first edge
+    //
+    // # Arguments
+    // * `graph` - The de Bruijn graph
+    //
+    // # Returns
+    // A vector with the unitigs, covering every edge of the graph once
+    let mut used = vec![false; graph.edges.len()];
+    let mut unitigs = Vec::new();
+
+    for (e, edge) in graph.edges.iter().enumerate() {
+        if !is_one_in_one_out(graph, edge.from) {
+            unitigs.push(extend_unitig(graph, e, &mut used));
+        }
+    }
+    for e in 0..graph.edges.len() {
+        if !used[e] {
+            unitigs.push(extend_unitig(graph, e, &mut used));
+        }
+    }
+    unitigs
+}
@@ -0,0 +1,67 @@
+use crate::debruijn::count_edges;
+use crate::eulerian::{find_eulerian_walk, spell_walk, Multigraph};
+use crate::kmer::populate_kmers;
+
+// Largest number of k-mers, |alphabet|^k, the string is built from. Every
+// k-mer is kept in memory along with the edges of the graph, so larger
+// strings would not fit in memory. 4^11 allows DNA strings up to k = 11
+const MAX_KMERS: usize = 1 << 22;
+
+pub fn run_universal(k: usize, alphabet: &str, linear: bool) -> Result<String, String> {
+    // Generate a k-universal circular string, which contains every k-mer over
+    // the alphabet exactly once. The k-mers are the edges of the complete de
+    // Bruijn graph, whose Eulerian cycle spells the string
+    //
+    // # Arguments
+    // * `k` - The length of the k-mers
+    // * `alphabet` - The symbols the k-mers are made of
+    // * `linear` - Return a linear string with every k-mer instead, by
+    //              repeating the first k-1 symbols at the end
+    //
+    // # Returns
+    // Result containing the string or an error message
+    if !alphabet.is_ascii() {
+        return Err(format!("The alphabet must be ASCII: {}", alphabet));
+    }
+    let mut symbols: Vec<char> = alphabet.chars().collect();
+    symbols.sort();
+    symbols.dedup();
+    if symbols.len() != alphabet.chars().count() {
+        return Err(format!("Repeated symbols in the alphabet: {}", alphabet));
+    }
+    if symbols.is_empty() || k == 0 {
+        return Err("The alphabet and k must not be empty".to_string());
+    }
+    if k == 1 {
+        return Ok(symbols.into_iter().collect());
+    }
+    let size = u32::try_from(k)
+        .ok()
+        .and_then(|k| symbols.len().checked_pow(k));
+    if size.is_none_or(|size| size > MAX_KMERS) {
+        return Err(format!(
+            "Too many k-mers: {}^{} is more than {}",
+            symbols.len(),
+            k,
+            MAX_KMERS
+        ));
+    }
+
+    let mut kmers = Vec::new();
+    populate_kmers(0, "".to_string(), &mut kmers, k, &symbols);
+    let edges = count_edges(
+        kmers.iter().map(|kmer| kmer.as_str()).collect(),
+        Some(k - 1),
+        false,
+    )?;
+
+    let graph = Multigraph::from_edge_counts(edges);
+    let (walk, _) = find_eulerian_walk(&graph)?;
+    let spelled = spell_walk(&walk)?;
+
+    // The spelled cycle ends with its first k-1 symbols again
+    if linear {
+        return Ok(spelled);
+    }
+    Ok(spelled[..spelled.len() - (k - 1)].to_string())
+}